@@ -88,4 +88,197 @@ pub enum EcomError{
 
     #[msg("Member does not belong to this organization.")]
     InvalidOrganization,
+
+    // Escrow Release Errors
+    #[msg("InvalidSeller: The destination does not match the seller recorded on the escrow.")]
+    InvalidSeller,
+
+    #[msg("InvalidBuyer: The signer is not the buyer recorded on the escrow.")]
+    InvalidBuyer,
+
+    #[msg("InvalidShippingWindow: The shipping window is shorter than the marketplace minimum.")]
+    InvalidShippingWindow,
+
+    #[msg("ShippingDeadlineNotReached: The escrow cannot expire before its shipping deadline.")]
+    ShippingDeadlineNotReached,
+
+    #[msg("OrderAlreadyShipped: The order has already shipped and can no longer expire.")]
+    OrderAlreadyShipped,
+
+    // Dispute Errors
+    #[msg("NotDisputeParty: Only the buyer or seller of the escrow can take this action.")]
+    NotDisputeParty,
+
+    #[msg("DisputeNotOpen: The dispute is not open.")]
+    DisputeNotOpen,
+
+    #[msg("EvidenceLimitReached: You have already attached the maximum evidence to this dispute.")]
+    EvidenceLimitReached,
+
+    #[msg("EvidenceUriTooLong: The evidence URI exceeds the maximum length.")]
+    EvidenceUriTooLong,
+
+    #[msg("InvalidArbitrator: The signer is not the marketplace arbitrator.")]
+    InvalidArbitrator,
+
+    #[msg("InvalidSplit: The buyer share must be between 0 and 10000 basis points.")]
+    InvalidSplit,
+
+    // Token Payment Errors
+    #[msg("Unauthorized: The signer is not the marketplace authority.")]
+    Unauthorized,
+
+    #[msg("MintNotAccepted: The mint is not on the marketplace allowlist.")]
+    MintNotAccepted,
+
+    #[msg("AcceptedMintsFull: The marketplace cannot accept any more mints.")]
+    AcceptedMintsFull,
+
+    #[msg("InvalidPaymentMethod: SOL cannot be registered as a token payment method.")]
+    InvalidPaymentMethod,
+
+    #[msg("InvalidMint: The mint does not match the payment record.")]
+    InvalidMint,
+
+    #[msg("MissingTokenAccounts: Token payments require the mint, token accounts and token program.")]
+    MissingTokenAccounts,
+
+    #[msg("InvalidTokenAccount: The token account owner or mint does not match the escrow record.")]
+    InvalidTokenAccount,
+
+    #[msg("PriceMismatch: The amount does not match the product price times quantity.")]
+    PriceMismatch,
+
+    #[msg("InvalidQuantity: Quantity must be greater than zero.")]
+    InvalidQuantity,
+
+    #[msg("InsufficientStock: The requested quantity exceeds the available stock.")]
+    InsufficientStock,
+
+    // Order Errors
+    #[msg("UnauthorizedOrderUpdate: The signer is not allowed to set this tracking status.")]
+    UnauthorizedOrderUpdate,
+
+    #[msg("InvalidStatusTransition: Orders can only move forward one tracking status at a time.")]
+    InvalidStatusTransition,
+
+    #[msg("OrderMismatch: The order is not linked to this escrow.")]
+    OrderMismatch,
+
+    #[msg("OrderAlreadyExists: An order has already been placed for this payment.")]
+    OrderAlreadyExists,
+
+    #[msg("DeliveryRequiresConfirmation: Use confirm_delivery to mark an order as delivered.")]
+    DeliveryRequiresConfirmation,
+
+    // Checkout Errors
+    #[msg("EmptyCart: There is nothing in the cart to check out.")]
+    EmptyCart,
+
+    #[msg("TooManyCartItems: The cart has more items than a single order can hold.")]
+    TooManyCartItems,
+
+    #[msg("CartAccountsMismatch: Remaining accounts must be (cart, product) pairs in cart list order.")]
+    CartAccountsMismatch,
+
+    #[msg("MixedSellers: All items in a checkout must come from the same seller.")]
+    MixedSellers,
+
+    #[msg("CartFull: The cart cannot hold any more items.")]
+    CartFull,
+
+    // Marketplace Config Errors
+    #[msg("InvalidFee: The fee must be between 0 and 10000 basis points.")]
+    InvalidFee,
+
+    #[msg("ListingsDisabled: New product listings are disabled by the marketplace.")]
+    ListingsDisabled,
+
+    #[msg("SplPaymentsDisabled: Token payments are disabled by the marketplace.")]
+    SplPaymentsDisabled,
+
+    #[msg("CartCheckoutDisabled: Cart checkout is disabled by the marketplace.")]
+    CartCheckoutDisabled,
+
+    #[msg("DisputesDisabled: Disputes are disabled by the marketplace.")]
+    DisputesDisabled,
+
+    // Pause Errors
+    #[msg("ProgramPaused: The marketplace is paused by the admin.")]
+    ProgramPaused,
+
+    #[msg("SubsystemPaused: This part of the marketplace is paused by the admin.")]
+    SubsystemPaused,
+
+    // Seller Profile Errors
+    #[msg("InvalidDisplayName: The display name must be 1 to 50 characters.")]
+    InvalidDisplayName,
+
+    #[msg("LogoUriTooLong: The logo URI exceeds the maximum length.")]
+    LogoUriTooLong,
+
+    #[msg("SellerSuspended: The seller has been suspended by the marketplace.")]
+    SellerSuspended,
+
+    // Review Errors
+    #[msg("InvalidRating: The review score must be between 1 and 5.")]
+    InvalidRating,
+
+    #[msg("OrderNotDelivered: Only delivered orders can be reviewed.")]
+    OrderNotDelivered,
+
+    #[msg("ProductNotInOrder: The product is not part of this order.")]
+    ProductNotInOrder,
+
+    #[msg("ReviewUriTooLong: The review URI exceeds the maximum length.")]
+    ReviewUriTooLong,
+
+    // Catalogue Errors
+    #[msg("ProductPageMismatch: The product is not on the given catalogue page.")]
+    ProductPageMismatch,
+
+    #[msg("MissingProductPage: The seller's last catalogue page is required.")]
+    MissingProductPage,
+
+    #[msg("EscrowNotSettled: The escrow still holds funds for the buyer or seller.")]
+    EscrowNotSettled,
+
+    // Courier Errors
+    #[msg("CourierNotApproved: The courier is not on the marketplace allowlist.")]
+    CourierNotApproved,
+
+    #[msg("CouriersFull: The courier allowlist is full.")]
+    CouriersFull,
+
+    #[msg("InvalidCourier: The buyer or seller cannot act as the courier.")]
+    InvalidCourier,
+
+    // Stock Errors
+    #[msg("ProductAccountsMismatch: Remaining accounts must be the order's products in item order.")]
+    ProductAccountsMismatch,
+
+    #[msg("EscrowAlreadyFunded: The escrow is not awaiting a deposit.")]
+    EscrowAlreadyFunded,
+
+    #[msg("PaymentNonceUsed: Payment nonces must increase; this nonce has already been used.")]
+    PaymentNonceUsed,
+
+    #[msg("CurrencyMismatch: The payment currency does not match the one the product is priced in.")]
+    CurrencyMismatch,
+
+    // Listing Errors
+    #[msg("DescriptionTooLong: The product description exceeds 300 characters.")]
+    DescriptionTooLong,
+
+    #[msg("ImageUrlTooLong: The product image URL exceeds 150 characters.")]
+    ImageUrlTooLong,
+
+    #[msg("InvalidStockStatus: A product with no units on hand must be out of stock.")]
+    InvalidStockStatus,
+
+    #[msg("ProductAlreadyIndexed: The product is already listed under this category or division.")]
+    ProductAlreadyIndexed,
+
+    #[msg("IndexPagePayerMismatch: The emptied index page's original payer is required to close it.")]
+    IndexPagePayerMismatch,
 }
\ No newline at end of file
@@ -1,25 +1,24 @@
 use anchor_lang::prelude::*;
-use crate::states::{cart::{Cart, CartCreated, CartList, Stock}, Product};
+use crate::{error::EcomError, states::{cart::{Cart, CartCreated, CartList}, marketplace::{MarketplaceConfig, Subsystem}, Product, Stock}};
 #[derive(Accounts)]
-#[instruction(product_name: String)]
 pub struct AddToCart<'info> {
     #[account(mut)]
     pub consumer: Signer<'info>,
 
+    pub products:Account<'info,Product>,
+
     #[account(
         init_if_needed,
         payer = consumer,
         seeds = [
             b"cart", consumer.key().as_ref(), 
-            product_name.as_bytes()
+            products.key().as_ref()
         ],
         bump,
         space = 8 + Cart::INIT_SPACE
     )]
     pub cart: Account<'info, Cart>,
 
-    #[account(mut)]
-    pub products:Account<'info,Product>,
     #[account(
         init_if_needed,
         payer = consumer,
@@ -28,37 +27,47 @@ pub struct AddToCart<'info> {
         space = 8 + CartList::INIT_SPACE
     )]
     pub cart_list:Account<'info,CartList>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
     pub system_program: Program<'info, System>,
 }
 
 impl <'info> AddToCart <'info> {
     pub fn add_to_cart(
         &mut self,
-        product_name: String,
         quantity: u64,
-        seller_pubkey: Pubkey,
-        product_imgurl: String,
-        amount: u64,
         cart_bump:u8,
     ) -> Result<()>{
-        let product_id = self.products.product_id;
+        self.config.require_active(Subsystem::Orders)?;
+        require!(quantity > 0, EcomError::InvalidQuantity);
+        let product = &self.products;
+        require!(
+            product.stock_status != Stock::OutOfStock && quantity <= product.quantity as u64,
+            EcomError::InsufficientStock
+        );
+        let amount = product.price as u64;
         self.cart.set_inner(Cart 
             { 
-                product_id, 
-                product_name: product_name.clone(),
+                product_id: product.product_id, 
+                product_pubkey: product.key(),
+                product_name: product.product_name.clone(),
                 quantity, 
-                seller_pubkey, 
-                product_imgurl: product_imgurl.clone(),
+                seller_pubkey: product.seller_pubkey, 
+                product_imgurl: product.product_imgurl.clone(),
                 amount: vec![amount], 
-                stock_status: Stock::InStock,
+                stock_status: product.stock_status.clone(),
                 cart_bump,
             });
 
         emit!(CartCreated{
-            product_name,
+            product_name: product.product_name.clone(),
             amount,
             quantity,
-            seller: seller_pubkey,
+            seller: product.seller_pubkey,
         });
         Ok(())
     }
@@ -67,19 +76,9 @@ impl <'info> AddToCart <'info> {
         &mut self,
         cart_list_bump:u8,
     )->Result<()>{
-        let cart = &mut self.cart;
-        let current_cart_total: u64 = cart.amount.iter().copied().sum();
-        let quantity = cart.quantity;
-        if self.cart_list.cart_list.is_empty() {
-            self.cart_list.set_inner(CartList { 
-                cart_list: Vec::new(),
-                total_amount: current_cart_total * quantity,  
-                cart_list_bump 
-            });
-        } else {
-            let new_total = self.cart_list.total_amount.saturating_add(current_cart_total);
-            self.cart_list.total_amount = new_total * quantity;
-        }
-        Ok(())
+        let cart_key = self.cart.key();
+        let line_total = self.cart.line_total();
+        self.cart_list.cart_list_bump = cart_list_bump;
+        self.cart_list.upsert(cart_key, line_total)
     }
 }
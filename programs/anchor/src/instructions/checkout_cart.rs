@@ -0,0 +1,331 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{associated_token::AssociatedToken, token::{self, Mint, Token, TokenAccount, TransferChecked}};
+use crate::{error::EcomError, states::{cart::{Cart, CartList}, escrow::{Escrow, EscrowStatus}, marketplace::{MarketplaceConfig, Subsystem}, order::{Order, OrderCounter, OrderItem, OrderStatus, OrderTracking}, payment::{Payment, PaymentCounter, PaymentMethod, PaymentStatus}, seller::SellerProfile, vault::VaultState, Product}};
+use anchor_lang::solana_program::hash::{self};
+
+#[derive(Accounts)]
+#[instruction(payment_nonce: u64)]
+pub struct CheckoutCart<'info>{
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"cart_list", buyer.key().as_ref()],
+        bump = cart_list.cart_list_bump,
+    )]
+    pub cart_list: Account<'info,CartList>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+
+    // Profile of the seller every cart line must belong to
+    #[account(
+        seeds = [b"seller", seller_profile.seller.as_ref()],
+        bump = seller_profile.profile_bump,
+        constraint = !seller_profile.suspended @ EcomError::SellerSuspended,
+    )]
+    pub seller_profile: Account<'info,SellerProfile>,
+
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [
+            b"payment",
+            buyer.key().as_ref(),
+            &payment_nonce.to_le_bytes()
+        ],
+        bump,
+        space = 8 + Payment::INIT_SPACE
+    )]
+    pub payment: Account<'info,Payment>,
+
+    // Rejects reused nonces so payment addresses are never recycled
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        seeds = [b"payment_counter", buyer.key().as_ref()],
+        bump,
+        space = 8 + PaymentCounter::INIT_SPACE,
+    )]
+    pub payment_counter:Account<'info,PaymentCounter>,
+
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"escrow",payment.key().as_ref()],
+        bump,
+        space = 8 + Escrow::INIT_SPACE,
+    )]
+    pub escrow: Account<'info,Escrow>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = VaultState::INIT_SPACE,
+        seeds = [b"state", payment.key().as_ref()],
+        bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        seeds = [b"order_counter",buyer.key().as_ref()],
+        bump,
+        space = 8 + OrderCounter::INIT_SPACE,
+    )]
+    pub order_counter: Account<'info,OrderCounter>,
+
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [
+            b"order",
+            buyer.key().as_ref(),
+            &order_counter.order_count.to_le_bytes()
+        ],
+        bump,
+        space = 9 + Order::INIT_SPACE,
+    )]
+    pub order: Account<'info,Order>,
+
+    // SPL payments only
+    pub mint:Option<Account<'info,Mint>>,
+    #[account(
+        init,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub buyer_token_account:Option<Account<'info,TokenAccount>>,
+    pub token_program:Option<Program<'info,Token>>,
+    pub associated_token_program:Option<Program<'info,AssociatedToken>>,
+    pub system_program:Program<'info,System>,
+}
+
+impl <'info> CheckoutCart<'info> {
+    /// `remaining_accounts` holds a writable (cart, product) pair for every
+    /// entry of the cart list, in cart list order.
+    pub fn checkout_cart(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        payment_nonce: u64,
+        expected_total: u64,
+        bumps: &CheckoutCartBumps,
+    ) -> Result<()> {
+        self.config.require_active(Subsystem::Payments)?;
+        self.config.require_active(Subsystem::Escrow)?;
+        self.config.require_active(Subsystem::Orders)?;
+        require!(self.config.cart_checkout_enabled, EcomError::CartCheckoutDisabled);
+        let clock = Clock::get()?;
+        self.payment_counter.use_nonce(payment_nonce)?;
+        self.payment_counter.buyer = self.buyer.key();
+        self.payment_counter.counter_bump = bumps.payment_counter;
+        let buyer_key = self.buyer.key();
+        let account_keys: Vec<Pubkey> = remaining_accounts.iter().map(|info| info.key()).collect();
+        let cart_keys = self.cart_list.checkout_keys(&account_keys)?;
+        let ship_deadline = self.config.ship_deadline(clock.unix_timestamp)?;
+
+        let mut items: Vec<OrderItem> = Vec::with_capacity(cart_keys.len());
+        let mut seller: Option<Pubkey> = None;
+        let mut total: u64 = 0;
+        let mut total_quantity: u32 = 0;
+        let payment_mint = self.mint.as_ref().map(|mint| mint.key());
+
+        for pair in remaining_accounts.chunks(2) {
+            let (cart_info, product_info) = (&pair[0], &pair[1]);
+
+            let cart = Account::<Cart>::try_from(cart_info)?;
+            let mut product = Account::<Product>::try_from(product_info)?;
+            let expected_cart = Pubkey::create_program_address(
+                &[
+                    b"cart",
+                    buyer_key.as_ref(),
+                    product.key().as_ref(),
+                    &[cart.cart_bump],
+                ],
+                &crate::ID,
+            ).map_err(|_| EcomError::CartAccountsMismatch)?;
+            require_keys_eq!(expected_cart, cart.key(), EcomError::CartAccountsMismatch);
+
+            match seller {
+                None => seller = Some(product.seller_pubkey),
+                Some(seller) => require_keys_eq!(seller, product.seller_pubkey, EcomError::MixedSellers),
+            }
+
+            require!(product.mint == payment_mint, EcomError::CurrencyMismatch);
+
+            let cart_price = cart.amount.last().copied().unwrap_or_default();
+            require!(cart_price == product.price as u64, EcomError::PriceMismatch);
+            let quantity = u32::try_from(cart.quantity).map_err(|_| EcomError::InvalidQuantity)?;
+            product.reserve_stock(quantity)?;
+
+            let line_total = (product.price as u64)
+                .checked_mul(quantity as u64)
+                .ok_or(EcomError::PriceMismatch)?;
+            total = total.checked_add(line_total).ok_or(EcomError::PriceMismatch)?;
+            total_quantity = total_quantity
+                .checked_add(quantity)
+                .ok_or(EcomError::InvalidQuantity)?;
+            items.push(OrderItem {
+                product: product.key(),
+                quantity,
+                price: product.price,
+            });
+
+            product.exit(&crate::ID)?;
+            cart.close(self.buyer.to_account_info())?;
+        }
+        require!(total == expected_total, EcomError::PriceMismatch);
+        let seller = seller.ok_or(EcomError::EmptyCart)?;
+        require_keys_eq!(self.seller_profile.seller, seller, EcomError::InvalidSeller);
+
+        let (payment_method, mint) = match &self.mint {
+            Some(mint) => {
+                require!(self.config.spl_payments_enabled, EcomError::SplPaymentsDisabled);
+                let accepted = self.config
+                    .accepted_mint(&mint.key())
+                    .ok_or(EcomError::MintNotAccepted)?;
+                require!(self.vault_token_account.is_some(), EcomError::MissingTokenAccounts);
+                (accepted.payment_method.clone(), Some(mint.key()))
+            }
+            None => (PaymentMethod::SOL, None),
+        };
+
+        let seed_data = [
+            buyer_key.as_ref(),
+            &payment_nonce.to_le_bytes(),
+            &clock.unix_timestamp.to_le_bytes(),
+        ].concat();
+        let hash = hash::hash(&seed_data);
+        let payment_id:[u8;16] = hash.to_bytes()[..16]
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        self.payment.set_inner(Payment {
+            payment_id,
+            owner: buyer_key,
+            payment_nonce,
+            payment_amount: total,
+            // Multi-line checkouts have no single product; the order holds the lines.
+            product_pubkey: None,
+            quantity: total_quantity,
+            escrow: Some(self.escrow.key()),
+            payment_method,
+            mint,
+            payment_status: PaymentStatus::Pending,
+            time_stamp: clock.unix_timestamp,
+            tx_signature: None,
+            payment_bump: bumps.payment,
+        });
+
+        self.escrow.set_inner(Escrow {
+            owner: buyer_key,
+            payment: self.payment.key(),
+            order: Some(self.order.key()),
+            buyer_pubkey: buyer_key,
+            seller_pubkey: seller,
+            amount: total,
+            release_fund: true,
+            time_stamp: clock.unix_timestamp,
+            update_timestamp: clock.unix_timestamp,
+            ship_deadline,
+            seller_payout: 0,
+            platform_fee: 0,
+            escrow_status: EscrowStatus::FundsReceived,
+            escrow_bump: bumps.escrow,
+        });
+        self.vault_state.state_bump = bumps.vault_state;
+        self.vault_state.vault_bump = bumps.vault;
+
+        self.deposit(total)?;
+
+        let order_index = self.order_counter.order_count;
+        let seed_data = [
+            buyer_key.as_ref(),
+            &order_index.to_le_bytes(),
+            &clock.unix_timestamp.to_le_bytes(),
+        ].concat();
+        let hash = hash::hash(&seed_data);
+        let order_id: [u8;16] = hash.to_bytes()[..16]
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        self.order.set_inner(Order {
+            order_id,
+            buyer: buyer_key,
+            seller,
+            courier: None,
+            order_index,
+            payment: self.payment.key(),
+            escrow: self.escrow.key(),
+            items,
+            tracking_id: order_id,
+            order_status: OrderStatus::Placed,
+            order_tracking: OrderTracking::Booked,
+            created_at: clock.unix_timestamp,
+            updated_at: clock.unix_timestamp,
+            order_bump: bumps.order,
+        });
+
+        self.order_counter.buyer = buyer_key;
+        self.order_counter.order_count = order_index
+            .checked_add(1)
+            .ok_or(EcomError::UnexpectedError)?;
+        self.order_counter.counter_bump = bumps.order_counter;
+
+        self.cart_list.clear();
+
+        msg!(
+            "Checked out {} cart items for {} into order {}",
+            cart_keys.len(),
+            total,
+            self.order.key()
+        );
+        Ok(())
+    }
+
+    fn deposit(&self, amount: u64) -> Result<()> {
+        let Some(expected_mint) = self.payment.mint else {
+            let cpi_accounts = system_program::Transfer {
+                from: self.buyer.to_account_info(),
+                to: self.vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+            return system_program::transfer(cpi_ctx, amount);
+        };
+        let (Some(mint), Some(from), Some(to), Some(token_program)) = (
+            &self.mint,
+            &self.buyer_token_account,
+            &self.vault_token_account,
+            &self.token_program,
+        ) else {
+            return err!(EcomError::MissingTokenAccounts);
+        };
+        require!(
+            from.owner == self.buyer.key() && from.mint == expected_mint,
+            EcomError::InvalidTokenAccount
+        );
+        let cpi_accounts = TransferChecked {
+            from: from.to_account_info(),
+            mint: mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.buyer.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+        token::transfer_checked(cpi_ctx, amount, mint.decimals)
+    }
+}
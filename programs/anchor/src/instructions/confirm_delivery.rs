@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::{error::EcomError, instructions::release_to_seller, states::{escrow::{Escrow, EscrowStatus}, marketplace::{MarketplaceConfig, Subsystem}, order::{Order, OrderStatusChanged, OrderTracking}, payment::{Payment, PaymentStatus}, seller::SellerProfile, vault::VaultState}};
+
+#[derive(Accounts)]
+pub struct ConfirmDelivery<'info>{
+    // The buyer confirming receipt, or the courier attesting delivery
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = config.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    ///CHECK: Native SOL Seller Account, must match the escrow record
+    #[account(
+        mut,
+        address = escrow.seller_pubkey @ EcomError::InvalidSeller,
+    )]
+    pub seller_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"seller", escrow.seller_pubkey.as_ref()],
+        bump = seller_profile.profile_bump,
+    )]
+    pub seller_profile:Account<'info,SellerProfile>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"payment",
+            payment.owner.as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
+    )]
+    pub payment:Account<'info,Payment>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow",payment.key().as_ref()],
+        bump = escrow.escrow_bump,
+    )]
+    pub escrow: Account<'info,Escrow>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"order",
+            order.buyer.as_ref(),
+            &order.order_index.to_le_bytes()
+        ],
+        bump = order.order_bump,
+        constraint = order.escrow == escrow.key()
+            && escrow.order == Some(order.key()) @ EcomError::OrderMismatch,
+    )]
+    pub order: Account<'info,Order>,
+
+    #[account(
+        seeds = [b"state", payment.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    // SPL payments only
+    pub mint:Option<Account<'info,Mint>>,
+    #[account(mut)]
+    pub vault_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub seller_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub treasury_token_account:Option<Account<'info,TokenAccount>>,
+    pub token_program:Option<Program<'info,Token>>,
+    pub system_program:Program<'info,System>,
+}
+
+impl <'info> ConfirmDelivery<'info> {
+    pub fn confirm_delivery(&mut self) -> Result<()> {
+        self.config.require_active(Subsystem::Escrow)?;
+        self.config.require_active(Subsystem::Orders)?;
+        let amount = self.payment.payment_amount;
+        let old_status = self.order.order_tracking.clone();
+        require!(
+            self.order.can_set_tracking(&self.signer.key(), &OrderTracking::Delivered),
+            EcomError::UnauthorizedOrderUpdate
+        );
+        // A courier dropped from the allowlist can no longer release funds
+        require!(
+            self.signer.key() == self.order.buyer
+                || self.config.is_approved_courier(&self.signer.key()),
+            EcomError::CourierNotApproved
+        );
+        require!(
+            self.order.can_confirm_delivery(&self.signer.key()),
+            EcomError::InvalidStatusTransition
+        );
+        require!(
+            self.payment.payment_status == PaymentStatus::Pending,
+            EcomError::InvalidPayment
+        );
+        require!(
+            self.escrow.release_fund
+                && matches!(self.escrow.escrow_status, EscrowStatus::FundsReceived),
+            EcomError::FundsNotFound
+        );
+
+        release_to_seller(
+            &mut self.escrow,
+            &mut self.seller_profile,
+            &self.payment,
+            &self.config,
+            &self.system_program,
+            &self.vault,
+            &self.vault_state,
+            self.seller_account.to_account_info(),
+            self.treasury.to_account_info(),
+            &self.mint,
+            &self.vault_token_account,
+            &self.seller_token_account,
+            &self.treasury_token_account,
+            &self.token_program,
+            amount,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        self.payment.payment_status = PaymentStatus::Success;
+        self.escrow.escrow_status = EscrowStatus::SwapSuccess;
+        self.escrow.release_fund = false;
+        self.escrow.update_timestamp = now;
+        self.order.order_tracking = OrderTracking::Delivered;
+        self.order.updated_at = now;
+        self.seller_profile.record_completed_order(Some(now - self.order.created_at));
+        emit!(self.seller_profile.reputation());
+
+        emit!(OrderStatusChanged {
+            order: self.order.key(),
+            old_status,
+            new_status: OrderTracking::Delivered,
+            updated_by: self.signer.key(),
+            timestamp: now,
+        });
+        msg!(
+            "Delivery confirmed by {}, released {} to {} ({} platform fee)",
+            self.signer.key(),
+            self.escrow.seller_payout,
+            self.seller_account.key(),
+            self.escrow.platform_fee
+        );
+        Ok(())
+    }
+}
@@ -1,19 +1,56 @@
 use anchor_lang::prelude::*;
-use crate::{error::EcomError, states::order::{Order, OrderStatus, OrderTracking}};
+use crate::{error::EcomError, states::{escrow::{Escrow, EscrowStatus}, marketplace::{MarketplaceConfig, Subsystem}, order::{Order, OrderCounter, OrderItem, OrderStatus, OrderStatusChanged, OrderTracking}, payment::{Payment, PaymentStatus}}};
 use anchor_lang::solana_program::hash::{self};
 #[derive(Accounts)]
 pub struct CreateOrder<'info>{
     #[account(mut)]
     pub signer:Signer<'info>,
 
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [b"order_counter",signer.key().as_ref()],
+        bump,
+        space = 8 + OrderCounter::INIT_SPACE,
+    )]
+    pub order_counter:Account<'info,OrderCounter>,
+
     #[account(
         init,
         payer = signer,
-        seeds = [b"order",signer.key().as_ref()],
+        seeds = [
+            b"order",
+            signer.key().as_ref(),
+            &order_counter.order_count.to_le_bytes()
+        ],
         bump,
         space = 9 + Order::INIT_SPACE,
     )]
     pub order:Account<'info,Order>,
+
+    #[account(
+        seeds = [
+            b"payment",
+            signer.key().as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
+    )]
+    pub payment:Account<'info,Payment>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow",payment.key().as_ref()],
+        bump = escrow.escrow_bump,
+        constraint = escrow.buyer_pubkey == signer.key() @ EcomError::InvalidBuyer,
+    )]
+    pub escrow:Account<'info,Escrow>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
     pub system_program:Program<'info,System>,
 }
 
@@ -24,10 +61,43 @@ pub struct UpdateOrder<'info>{
 
     #[account(
         mut,
-        seeds = [b"order",signer.key().as_ref()],
-        bump,
+        seeds = [
+            b"order",
+            order.buyer.as_ref(),
+            &order.order_index.to_le_bytes()
+        ],
+        bump = order.order_bump,
+    )]
+    pub order:Account<'info,Order>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AssignCourier<'info>{
+    pub seller:Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"order",
+            order.buyer.as_ref(),
+            &order.order_index.to_le_bytes()
+        ],
+        bump = order.order_bump,
+        constraint = order.seller == seller.key() @ EcomError::UnauthorizedOrderUpdate,
     )]
     pub order:Account<'info,Order>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
 }
 
 #[derive(Accounts)]
@@ -37,23 +107,42 @@ pub struct CloseOrder<'info>{
     #[account(
         mut,
         close = signer,
-        seeds = [b"order",signer.key().as_ref()],
-        bump 
+        seeds = [
+            b"order",
+            signer.key().as_ref(),
+            &order.order_index.to_le_bytes()
+        ],
+        bump = order.order_bump,
     )]
     pub order:Account<'info,Order>,
+
+    ///CHECK: The order's escrow; it must be settled, or already closed by close_all
+    #[account(address = order.escrow @ EcomError::OrderMismatch)]
+    pub escrow:AccountInfo<'info>,
     pub system_program: Program<'info,System>
 }
 
 impl<'info> CreateOrder<'info> {
     pub fn create_order(
         &mut self,
-        payment_id:String,
         order_bump:u8,
+        counter_bump:u8,
     ) -> Result<()> {
+        self.config.require_active(Subsystem::Orders)?;
         let clock = Clock::get()?;
+        require!(
+            self.payment.payment_status == PaymentStatus::Pending
+                && self.escrow.release_fund
+                && matches!(self.escrow.escrow_status, EscrowStatus::FundsReceived),
+            EcomError::FundsNotFound
+        );
+        require!(self.escrow.order.is_none(), EcomError::OrderAlreadyExists);
+        let product = self.payment.product_pubkey.ok_or(EcomError::InvalidPayment)?;
+        let order_index = self.order_counter.order_count;
 
         let seed_data = [
             self.signer.key().as_ref(),
+            &order_index.to_le_bytes(),
             &clock.unix_timestamp.to_le_bytes(),
         ].concat();
         
@@ -68,8 +157,18 @@ impl<'info> CreateOrder<'info> {
         .map_err(|_| ProgramError::InvalidInstructionData)?;
         
         self.order.set_inner(Order { 
-            order_id: order_id,
-            payment_id, 
+            order_id,
+            buyer: self.signer.key(),
+            seller: self.escrow.seller_pubkey,
+            courier: None,
+            order_index,
+            payment: self.payment.key(),
+            escrow: self.escrow.key(),
+            items: vec![OrderItem {
+                product,
+                quantity: self.payment.quantity,
+                price: (self.payment.payment_amount / self.payment.quantity as u64) as u32,
+            }],
             tracking_id, 
             order_status:OrderStatus::Placed, 
             order_tracking:OrderTracking::Booked, 
@@ -77,25 +176,83 @@ impl<'info> CreateOrder<'info> {
             updated_at:clock.unix_timestamp, 
             order_bump,
         });
+
+        self.escrow.order = Some(self.order.key());
+
+        self.order_counter.buyer = self.signer.key();
+        self.order_counter.order_count = order_index
+            .checked_add(1)
+            .ok_or(EcomError::UnexpectedError)?;
+        self.order_counter.counter_bump = counter_bump;
        Ok(()) 
     }
 }
 
 impl<'info> UpdateOrder<'info> {
-    pub fn update_tracking_status(&mut self, status_str: String) -> Result<()> {
-        match status_str.as_str() {
-            "intransit" => self.order.order_tracking = OrderTracking::InTransit,
-            "shipped" => self.order.order_tracking = OrderTracking::Shipped,
-            "outfordelivery" => self.order.order_tracking = OrderTracking::OutForDelivery,
-            "delivered" => self.order.order_tracking = OrderTracking::Delivered,
-            _ => return err!(EcomError::UnexpectedError), 
+    pub fn update_tracking_status(&mut self, new_status: OrderTracking) -> Result<()> {
+        self.config.require_active(Subsystem::Orders)?;
+        let old_status = self.order.order_tracking.clone();
+        require!(
+            new_status != OrderTracking::Delivered,
+            EcomError::DeliveryRequiresConfirmation
+        );
+        require!(
+            old_status.can_transition_to(&new_status),
+            EcomError::InvalidStatusTransition
+        );
+        require!(
+            self.order.can_set_tracking(&self.signer.key(), &new_status),
+            EcomError::UnauthorizedOrderUpdate
+        );
+        require!(
+            !self.order.is_courier(&self.signer.key())
+                || self.config.is_approved_courier(&self.signer.key()),
+            EcomError::CourierNotApproved
+        );
+        let now = Clock::get()?.unix_timestamp;
+        self.order.order_tracking = new_status.clone();
+        self.order.updated_at = now;
+
+        emit!(OrderStatusChanged {
+            order: self.order.key(),
+            old_status,
+            new_status,
+            updated_by: self.signer.key(),
+            timestamp: now,
+        });
+        Ok(())
+    }
+}
+
+impl<'info> AssignCourier<'info> {
+    pub fn assign_courier(&mut self, courier: Option<Pubkey>) -> Result<()> {
+        self.config.require_active(Subsystem::Orders)?;
+        if let Some(courier) = courier {
+            require!(
+                courier != self.order.seller && courier != self.order.buyer,
+                EcomError::InvalidCourier
+            );
+            require!(
+                self.config.is_approved_courier(&courier),
+                EcomError::CourierNotApproved
+            );
         }
+        self.order.courier = courier;
+        self.order.updated_at = Clock::get()?.unix_timestamp;
         Ok(())
     }
 }
 
 impl <'info>CloseOrder<'info> {
     pub fn close_order(&mut self)->Result<()>{
+        // Only an escrow that links back to this order holds its funds
+        if self.escrow.owner == &crate::ID && !self.escrow.data_is_empty() {
+            let escrow = Escrow::try_deserialize(&mut &self.escrow.try_borrow_data()?[..])?;
+            require!(
+                escrow.order != Some(self.order.key()) || escrow.is_settled(),
+                EcomError::EscrowNotSettled
+            );
+        }
         msg!("Order Closed Successfullt, {}",self.order.key());
         Ok(())
     }
@@ -1,9 +1,11 @@
 use anchor_lang::{prelude::*, system_program::{self, Transfer, transfer}};
-use crate::{error::EcomError, states::{escrow::{Escrow, EscrowStatus}, payment::{Payment, PaymentMethod, PaymentStatus}, vault::VaultState}};
+use anchor_spl::{associated_token::{get_associated_token_address, AssociatedToken}, token::{self, CloseAccount, Mint, Token, TokenAccount, TransferChecked}};
+use crate::{error::EcomError, states::{escrow::{Escrow, EscrowStatus, FundsReleased}, marketplace::{MarketplaceConfig, Subsystem}, product::{Product, Stock}, payment::{Payment, PaymentCounter, PaymentMethod, PaymentStatus}, seller::SellerProfile, vault::VaultState}};
 use anchor_lang::solana_program::hash::{self};
 
 
 #[derive(Accounts)]
+#[instruction(payment_nonce: u64)]
 pub struct CreatePayment<'info>{
     #[account(mut)]
     pub signer:Signer<'info>,
@@ -11,11 +13,36 @@ pub struct CreatePayment<'info>{
     #[account(
         init,
         payer = signer,
-        seeds = [b"payment",signer.key().as_ref()],
+        seeds = [
+            b"payment",
+            signer.key().as_ref(),
+            &payment_nonce.to_le_bytes()
+        ],
         bump,
         space = 8 + Payment::INIT_SPACE
     )]
     pub payments:Account<'info,Payment>,
+
+    // Rejects reused nonces so payment addresses are never recycled
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [b"payment_counter", signer.key().as_ref()],
+        bump,
+        space = 8 + PaymentCounter::INIT_SPACE,
+    )]
+    pub payment_counter:Account<'info,PaymentCounter>,
+
+    pub product:Account<'info,Product>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config:Account<'info,MarketplaceConfig>,
+
+    // SPL payments only, must be on the marketplace allowlist
+    pub mint:Option<Account<'info,Mint>>,
     pub system_program:Program<'info,System>
 }
 
@@ -23,27 +50,44 @@ pub struct CreatePayment<'info>{
 pub struct CreateEscrow<'info>{
     #[account(mut)]
     pub owner:Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"payment",
+            owner.key().as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
+    )]
+    pub payment:Account<'info,Payment>,
+
     #[account(
         init,
         payer = owner,
-        seeds = [b"escrow",owner.key().as_ref()],
+        seeds = [b"escrow",payment.key().as_ref()],
         bump,
         space = 8 + Escrow::INIT_SPACE,
     )]
     pub escrow: Account<'info,Escrow>,
 
+    // The purchased product; its lister is the escrow seller
     #[account(
-        mut,
-        seeds = [b"payment",owner.key().as_ref()],
-        bump,
+        constraint = payment.product_pubkey == Some(product.key()) @ EcomError::InvalidPayment,
     )]
-    pub payment:Account<'info,Payment>,
+    pub product:Account<'info,Product>,
+
+    #[account(
+        seeds = [b"seller", product.seller_pubkey.as_ref()],
+        bump = seller_profile.profile_bump,
+        constraint = !seller_profile.suspended @ EcomError::SellerSuspended,
+    )]
+    pub seller_profile:Account<'info,SellerProfile>,
 
     #[account(
         init, 
         payer = owner, 
         space = VaultState::INIT_SPACE, 
-        seeds = [b"state", owner.key().as_ref()], 
+        seeds = [b"state", payment.key().as_ref()], 
         bump,
     )]
     pub vault_state: Account<'info, VaultState>,
@@ -53,6 +97,24 @@ pub struct CreateEscrow<'info>{
         bump, 
     )]
     pub vault: SystemAccount<'info>, //
+
+    // SPL payments only: the vault PDA's associated token account
+    pub mint:Option<Account<'info,Mint>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account:Option<Account<'info,TokenAccount>>,
+    pub token_program:Option<Program<'info,Token>>,
+    pub associated_token_program:Option<Program<'info,AssociatedToken>>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
     pub system_program:Program<'info,System>
 }
 
@@ -63,19 +125,30 @@ pub struct DepositeEscrow<'info>{
     pub owner: Signer<'info>,
     #[account(
         mut,
-        seeds = [b"escrow",owner.key().as_ref()],
-        bump,
+        seeds = [b"escrow",payment.key().as_ref()],
+        bump = escrow.escrow_bump,
     )]
     pub escrow: Account<'info,Escrow>,
     #[account(
         mut,
-        seeds = [b"payment",owner.key().as_ref()],
-        bump,
+        seeds = [
+            b"payment",
+            owner.key().as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
     )]
     pub payment:Account<'info,Payment>,
+
+    // The purchased product; its stock is reserved once the buyer pays
+    #[account(
+        mut,
+        constraint = payment.product_pubkey == Some(product.key()) @ EcomError::InvalidPayment,
+    )]
+    pub product:Account<'info,Product>,
     
     #[account(
-        seeds = [b"state", owner.key().as_ref()],
+        seeds = [b"state", payment.key().as_ref()],
         bump = vault_state.state_bump,
     )]
     pub vault_state: Account<'info, VaultState>,
@@ -94,6 +167,20 @@ pub struct DepositeEscrow<'info>{
     ///CHECK: Native SOL User Accounts
     #[account(mut)]
     pub user: AccountInfo<'info>,
+
+    // SPL payments only
+    pub mint:Option<Account<'info,Mint>>,
+    #[account(mut)]
+    pub user_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub vault_token_account:Option<Account<'info,TokenAccount>>,
+    pub token_program:Option<Program<'info,Token>>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
     pub system_program:Program<'info,System>,
 }
 
@@ -102,26 +189,53 @@ pub struct WithdrawlEscrow<'info>{
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    ///CHECK: Native SOL Seller Account
-    #[account(mut)]
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = config.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    ///CHECK: Native SOL Seller Account, must match the escrow record
+    #[account(
+        mut,
+        address = escrow.seller_pubkey @ EcomError::InvalidSeller,
+    )]
     pub seller_account: AccountInfo<'info>,
 
     #[account(
         mut,
-        seeds = [b"escrow",owner.key().as_ref()],
-        bump,
+        seeds = [b"seller", escrow.seller_pubkey.as_ref()],
+        bump = seller_profile.profile_bump,
+    )]
+    pub seller_profile:Account<'info,SellerProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow",payment.key().as_ref()],
+        bump = escrow.escrow_bump,
     )]
     pub escrow: Account<'info,Escrow>,
 
     #[account(
         mut,
-        seeds = [b"payment",owner.key().as_ref()],
-        bump,
+        seeds = [
+            b"payment",
+            owner.key().as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
     )]
     pub payment:Account<'info,Payment>,
     
     #[account(
-        seeds = [b"state", owner.key().as_ref()],
+        seeds = [b"state", payment.key().as_ref()],
         bump = vault_state.state_bump,
     )]
     pub vault_state: Account<'info, VaultState>,
@@ -133,6 +247,15 @@ pub struct WithdrawlEscrow<'info>{
     )]
     pub vault: SystemAccount<'info>,
 
+    // SPL payments only
+    pub mint:Option<Account<'info,Mint>>,
+    #[account(mut)]
+    pub vault_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub seller_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub treasury_token_account:Option<Account<'info,TokenAccount>>,
+    pub token_program:Option<Program<'info,Token>>,
     pub system_program:Program<'info,System>,
 }
 
@@ -145,17 +268,22 @@ pub struct CloseAll<'info> {
     #[account(
         mut,
         close = signer,
-        seeds = [b"payment", signer.key().as_ref()],
-        bump,
+        seeds = [
+            b"payment",
+            signer.key().as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
     )]
     pub payment: Account<'info, Payment>,
 
-    // escrow (close → refund rent)
+    // escrow (close → refund rent), only once nothing is held in it
     #[account(
         mut,
         close = signer,
-        seeds = [b"escrow", signer.key().as_ref()],
-        bump,
+        seeds = [b"escrow", payment.key().as_ref()],
+        bump = escrow.escrow_bump,
+        constraint = escrow.is_closable() @ EcomError::EscrowNotSettled,
     )]
     pub escrow: Account<'info, Escrow>,
 
@@ -163,12 +291,12 @@ pub struct CloseAll<'info> {
     #[account(
         mut,
         close = signer,
-        seeds = [b"state", signer.key().as_ref()],
+        seeds = [b"state", payment.key().as_ref()],
         bump = vault_state.state_bump,
     )]
     pub vault_state: Account<'info, VaultState>,
 
-    // vault PDA – drain all lamports then let the system close it
+    // vault PDA – sweep any leftover lamports; escrowed funds are already gone
     #[account(
         mut,
         seeds = [b"vault", vault_state.key().as_ref()],
@@ -176,21 +304,47 @@ pub struct CloseAll<'info> {
     )]
     pub vault: SystemAccount<'info>,
 
+    // SPL payments only: the vault token account is swept and closed as well
+    pub mint: Option<Account<'info, Mint>>,
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub signer_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> CreatePayment<'info>{
     pub fn create_payment(
         &mut self,
+        payment_nonce: u64,
         payment_amount: u64,
-        product_pubkey:Pubkey,
+        quantity:u32,
         tx_signature:Option<String>,
         payment_bump:u8,
+        counter_bump:u8,
     ) -> Result<()> {
+        self.config.require_active(Subsystem::Payments)?;
         let clock = Clock::get()?;
+        self.payment_counter.use_nonce(payment_nonce)?;
+        self.payment_counter.buyer = self.signer.key();
+        self.payment_counter.counter_bump = counter_bump;
+
+        require!(quantity > 0, EcomError::InvalidQuantity);
+        let expected_amount = (self.product.price as u64)
+            .checked_mul(quantity as u64)
+            .ok_or(EcomError::PriceMismatch)?;
+        require!(payment_amount == expected_amount, EcomError::PriceMismatch);
+        // Stock is only reserved once the escrow is funded
+        require!(
+            self.product.stock_status != Stock::OutOfStock && quantity <= self.product.quantity,
+            EcomError::InsufficientStock
+        );
 
         let seed_data = [
             self.signer.key().as_ref(),
+            &payment_nonce.to_le_bytes(),
             &clock.unix_timestamp.to_le_bytes(),
         ].concat();
         let hash = hash::hash(&seed_data);
@@ -198,12 +352,30 @@ impl<'info> CreatePayment<'info>{
             .try_into()
             .map_err(|_| ProgramError::InvalidInstructionData)?;
 
+        let (payment_method, mint) = match &self.mint {
+            Some(mint) => {
+                require!(self.config.spl_payments_enabled, EcomError::SplPaymentsDisabled);
+                let accepted = self.config
+                    .accepted_mint(&mint.key())
+                    .ok_or(EcomError::MintNotAccepted)?;
+                (accepted.payment_method.clone(), Some(mint.key()))
+            }
+            None => (PaymentMethod::SOL, None),
+        };
+        // Prices carry no decimals of their own, so pay in the listing's currency
+        require!(mint == self.product.mint, EcomError::CurrencyMismatch);
+
         self.payments.set_inner(
             Payment { 
-                payment_id: payment_id, 
+                payment_id, 
+                owner: self.signer.key(),
+                payment_nonce,
                 payment_amount, 
-                product_pubkey, 
-                payment_method: PaymentMethod::SOL, 
+                product_pubkey: Some(self.product.key()),
+                quantity,
+                escrow: None,
+                payment_method, 
+                mint,
                 payment_status: PaymentStatus::Pending, 
                 time_stamp: clock.unix_timestamp, 
                 tx_signature, 
@@ -217,31 +389,43 @@ impl<'info> CreatePayment<'info>{
 impl <'info> CreateEscrow<'info> {
     pub fn create_escrow(
         &mut self,
-        buyer_pubkey:Pubkey,
-        seller_pubkey:Pubkey,
         amount:u64,
         escrow_bump:u8,
         vault:u8,
         vault_state:u8
     )->Result<()> {
+        self.config.require_active(Subsystem::Escrow)?;
         let clock = Clock::get()?;
 
         require!(
             self.payment.payment_status == PaymentStatus::Pending,
             EcomError::InvalidPayment
         );
+        require!(amount == self.payment.payment_amount, EcomError::PriceMismatch);
+        if let Some(expected_mint) = self.payment.mint {
+            let mint = self.mint.as_ref().ok_or(EcomError::MissingTokenAccounts)?;
+            require_keys_eq!(mint.key(), expected_mint, EcomError::InvalidMint);
+            require!(self.vault_token_account.is_some(), EcomError::MissingTokenAccounts);
+        }
 
         self.escrow.set_inner(Escrow { 
             owner: self.owner.key(), 
-            buyer_pubkey, 
-            seller_pubkey, 
+            payment: self.payment.key(),
+            order: None,
+            buyer_pubkey: self.owner.key(),
+            seller_pubkey: self.product.seller_pubkey,
             amount, 
             release_fund: false, 
             time_stamp: clock.unix_timestamp, 
             update_timestamp :clock.unix_timestamp, 
+            // Set from the marketplace shipping window once funded
+            ship_deadline: 0,
+            seller_payout: 0,
+            platform_fee: 0,
             escrow_status:EscrowStatus::SwapPending, 
             escrow_bump
         });
+        self.payment.escrow = Some(self.escrow.key());
         self.vault_state.state_bump = vault_state;
         self.vault_state.vault_bump = vault;
         Ok(())
@@ -249,10 +433,8 @@ impl <'info> CreateEscrow<'info> {
 }
 
 impl <'info> DepositeEscrow<'info> {
-    pub fn deposite_escrow(
-        &mut self,
-        _escrow_bump:u8,
-    )-> Result<()> {
+    pub fn deposite_escrow(&mut self)-> Result<()> {
+        self.config.require_active(Subsystem::Escrow)?;
         let payment = &mut self.payment;
         let escrow = &mut self.escrow;
         let amount = payment.payment_amount;
@@ -260,60 +442,263 @@ impl <'info> DepositeEscrow<'info> {
             payment.payment_status == PaymentStatus::Pending,
             EcomError::EscrowError
         );
-        let cpi_programs = self.system_program.to_account_info();
-        let cpi_accounts = system_program::Transfer {
-            from:self.user.to_account_info(),
-            to:self.vault.to_account_info(),
-        };
-        let cpi_ctx: CpiContext<'_, '_, '_, '_, system_program::Transfer<'_>> = CpiContext::new(cpi_programs,cpi_accounts);
-        system_program::transfer(cpi_ctx, amount)?;
+        // Deposit once, into an escrow that is still waiting for it
+        require!(
+            !escrow.release_fund
+                && matches!(escrow.escrow_status, EscrowStatus::SwapPending),
+            EcomError::EscrowAlreadyFunded
+        );
+        self.product.reserve_stock(payment.quantity)?;
+        match payment.mint {
+            None => {
+                let cpi_programs = self.system_program.to_account_info();
+                let cpi_accounts = system_program::Transfer {
+                    from:self.user.to_account_info(),
+                    to:self.vault.to_account_info(),
+                };
+                let cpi_ctx: CpiContext<'_, '_, '_, '_, system_program::Transfer<'_>> = CpiContext::new(cpi_programs,cpi_accounts);
+                system_program::transfer(cpi_ctx, amount)?;
+            }
+            Some(expected_mint) => {
+                let (Some(mint), Some(from), Some(to), Some(token_program)) = (
+                    &self.mint,
+                    &self.user_token_account,
+                    &self.vault_token_account,
+                    &self.token_program,
+                ) else {
+                    return err!(EcomError::MissingTokenAccounts);
+                };
+                require_keys_eq!(mint.key(), expected_mint, EcomError::InvalidMint);
+                require_keys_eq!(
+                    to.key(),
+                    get_associated_token_address(&self.vault.key(), &expected_mint),
+                    EcomError::InvalidTokenAccount
+                );
+                require!(
+                    from.owner == self.owner.key() && from.mint == expected_mint,
+                    EcomError::InvalidTokenAccount
+                );
+                let cpi_accounts = TransferChecked {
+                    from: from.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.owner.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+                token::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+            }
+        }
 
         escrow.escrow_status = EscrowStatus::FundsReceived;
         escrow.release_fund = true;
+        escrow.ship_deadline = self.config.ship_deadline(Clock::get()?.unix_timestamp)?;
         Ok(())
     }
 }
 
 impl <'info> WithdrawlEscrow<'info> {
     pub fn withdrawl_escrow(&mut self) -> Result<()> {
+        self.config.require_active(Subsystem::Escrow)?;
         let amount = self.payment.payment_amount;
         require!(
-            self.payment.payment_status == PaymentStatus::Pending
-                && self.payment.payment_method == PaymentMethod::SOL,
+            self.payment.payment_status == PaymentStatus::Pending,
             EcomError::InvalidPayment
         );
-        require!(self.escrow.release_fund, EcomError::FundsNotFound);
-
-        let state_value_key = self.vault_state.key();
-        let seeds: &[&[u8]] = &[
-            b"vault",
-            state_value_key.as_ref(),
-            &[self.vault_state.vault_bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        let cpi_accounts = Transfer {
-            from: self.vault.to_account_info(),
-            to: self.seller_account.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new_with_signer(
-            self.system_program.to_account_info(),
-            cpi_accounts,
-            signer_seeds,
+        // A disputed escrow can only be settled by the arbitrator
+        require!(
+            self.escrow.release_fund
+                && matches!(self.escrow.escrow_status, EscrowStatus::FundsReceived),
+            EcomError::FundsNotFound
         );
-        transfer(cpi_ctx, amount)?;
+
+        release_to_seller(
+            &mut self.escrow,
+            &mut self.seller_profile,
+            &self.payment,
+            &self.config,
+            &self.system_program,
+            &self.vault,
+            &self.vault_state,
+            self.seller_account.to_account_info(),
+            self.treasury.to_account_info(),
+            &self.mint,
+            &self.vault_token_account,
+            &self.seller_token_account,
+            &self.treasury_token_account,
+            &self.token_program,
+            amount,
+        )?;
 
         self.payment.payment_status = PaymentStatus::Success;
         self.escrow.escrow_status = EscrowStatus::SwapSuccess;
         self.escrow.release_fund = false;
+        self.escrow.update_timestamp = Clock::get()?.unix_timestamp;
+        self.seller_profile.record_completed_order(None);
+        emit!(self.seller_profile.reputation());
 
         Ok(())
     }
 }
 
+/// Moves `amount` lamports out of the vault PDA, signing with its seeds.
+pub fn transfer_from_vault<'info>(
+    system_program: &Program<'info, System>,
+    vault: &SystemAccount<'info>,
+    vault_state: &Account<'info, VaultState>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let state_value_key = vault_state.key();
+    let seeds: &[&[u8]] = &[
+        b"vault",
+        state_value_key.as_ref(),
+        &[vault_state.vault_bump],
+    ];
+    let signer_seeds = &[seeds];
+
+    let cpi_accounts = Transfer {
+        from: vault.to_account_info(),
+        to,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    transfer(cpi_ctx, amount)
+}
+
+/// Pays `amount` out of the escrow vault to `recipient`: as lamports for SOL
+/// payments, or from the vault's associated token account into the
+/// recipient's token account for SPL payments.
+pub fn payout_from_vault<'info>(
+    payment: &Payment,
+    system_program: &Program<'info, System>,
+    vault: &SystemAccount<'info>,
+    vault_state: &Account<'info, VaultState>,
+    recipient: AccountInfo<'info>,
+    mint: &Option<Account<'info, Mint>>,
+    vault_token_account: &Option<Account<'info, TokenAccount>>,
+    recipient_token_account: &Option<Account<'info, TokenAccount>>,
+    token_program: &Option<Program<'info, Token>>,
+    amount: u64,
+) -> Result<()> {
+    let Some(expected_mint) = payment.mint else {
+        return transfer_from_vault(system_program, vault, vault_state, recipient, amount);
+    };
+    let (Some(mint), Some(from), Some(to), Some(token_program)) =
+        (mint, vault_token_account, recipient_token_account, token_program)
+    else {
+        return err!(EcomError::MissingTokenAccounts);
+    };
+    require_keys_eq!(mint.key(), expected_mint, EcomError::InvalidMint);
+    require_keys_eq!(
+        from.key(),
+        get_associated_token_address(&vault.key(), &expected_mint),
+        EcomError::InvalidTokenAccount
+    );
+    require!(
+        to.owner == recipient.key() && to.mint == expected_mint,
+        EcomError::InvalidTokenAccount
+    );
+
+    let state_value_key = vault_state.key();
+    let seeds: &[&[u8]] = &[
+        b"vault",
+        state_value_key.as_ref(),
+        &[vault_state.vault_bump],
+    ];
+    let signer_seeds = &[seeds];
+
+    let cpi_accounts = TransferChecked {
+        from: from.to_account_info(),
+        mint: mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    token::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+/// Releases `amount` from the vault to the seller, routing the marketplace
+/// fee to the treasury, and records the split on the escrow and the sale on
+/// the seller's profile.
+pub fn release_to_seller<'info>(
+    escrow: &mut Account<'info, Escrow>,
+    seller_profile: &mut Account<'info, SellerProfile>,
+    payment: &Payment,
+    config: &MarketplaceConfig,
+    system_program: &Program<'info, System>,
+    vault: &SystemAccount<'info>,
+    vault_state: &Account<'info, VaultState>,
+    seller: AccountInfo<'info>,
+    treasury: AccountInfo<'info>,
+    mint: &Option<Account<'info, Mint>>,
+    vault_token_account: &Option<Account<'info, TokenAccount>>,
+    seller_token_account: &Option<Account<'info, TokenAccount>>,
+    treasury_token_account: &Option<Account<'info, TokenAccount>>,
+    token_program: &Option<Program<'info, Token>>,
+    amount: u64,
+) -> Result<()> {
+    let (seller_payout, platform_fee) = config.split_fee(amount);
+    let seller_key = seller.key();
+    let treasury_key = treasury.key();
+
+    if seller_payout > 0 {
+        payout_from_vault(
+            payment,
+            system_program,
+            vault,
+            vault_state,
+            seller,
+            mint,
+            vault_token_account,
+            seller_token_account,
+            token_program,
+            seller_payout,
+        )?;
+    }
+    if platform_fee > 0 {
+        payout_from_vault(
+            payment,
+            system_program,
+            vault,
+            vault_state,
+            treasury,
+            mint,
+            vault_token_account,
+            treasury_token_account,
+            token_program,
+            platform_fee,
+        )?;
+    }
+
+    escrow.seller_payout = seller_payout;
+    escrow.platform_fee = platform_fee;
+    seller_profile.total_sales = seller_profile.total_sales.saturating_add(amount);
+    emit!(FundsReleased {
+        escrow: escrow.key(),
+        seller: seller_key,
+        treasury: treasury_key,
+        mint: payment.mint,
+        seller_payout,
+        platform_fee,
+        fee_bps: config.fee_bps,
+    });
+    Ok(())
+}
+
 impl<'info> CloseAll<'info> {
     pub fn close_all(&mut self) -> Result<()> {
-        // 1. Drain vault → signer
+        if self.payment.mint.is_some() {
+            self.close_vault_token_account()?;
+        }
+
+        // 1. Sweep leftover vault lamports → signer
         let lamports = self.vault.lamports();
         if lamports > 0 {
             let vault_state_key = self.vault_state.key();
@@ -333,7 +718,7 @@ impl<'info> CloseAll<'info> {
                 cpi_accounts,
                 signer_seeds,
             );
-            let _ =transfer(cpi_ctx, lamports)?;
+            transfer(cpi_ctx, lamports)?;
         }
 
         msg!(
@@ -345,4 +730,55 @@ impl<'info> CloseAll<'info> {
         );
         Ok(())
     }
+
+    /// Sends any tokens left in the vault's token account to the signer and
+    /// closes it, returning its rent.
+    fn close_vault_token_account(&self) -> Result<()> {
+        let (Some(vault_token_account), Some(token_program)) =
+            (&self.vault_token_account, &self.token_program)
+        else {
+            return err!(EcomError::MissingTokenAccounts);
+        };
+
+        let leftover = vault_token_account.amount;
+        if leftover > 0 {
+            payout_from_vault(
+                &self.payment,
+                &self.system_program,
+                &self.vault,
+                &self.vault_state,
+                self.signer.to_account_info(),
+                &self.mint,
+                &self.vault_token_account,
+                &self.signer_token_account,
+                &self.token_program,
+                leftover,
+            )?;
+        } else if let Some(expected_mint) = self.payment.mint {
+            require_keys_eq!(
+                vault_token_account.key(),
+                get_associated_token_address(&self.vault.key(), &expected_mint),
+                EcomError::InvalidTokenAccount
+            );
+        }
+
+        let state_value_key = self.vault_state.key();
+        let seeds: &[&[u8]] = &[
+            b"vault",
+            state_value_key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = CloseAccount {
+            account: vault_token_account.to_account_info(),
+            destination: self.signer.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        token::close_account(cpi_ctx)
+    }
 }
\ No newline at end of file
@@ -1,11 +1,17 @@
 use anchor_lang::prelude::*;
-use crate::states::{Category, Division, Product, ProductCreated, ProductsList, Stock};
+use crate::{error::EcomError, states::{marketplace::{MarketplaceConfig, Subsystem}, seller::SellerProfile, Category, Division, Product, ProductCreated, ProductIndex, ProductIndexPage, ProductPage, ProductsList, Stock}};
 use anchor_lang::solana_program::hash::{self};
 use anchor_lang::solana_program::program_error::ProgramError;
 
 
 #[derive(Accounts)]
-#[instruction(product_name: String)]
+#[instruction(
+    product_name: String,
+    product_short_description: String,
+    price: u32,
+    category: Category,
+    division: Division,
+)]
 pub struct CreateProduct<'info> {
     #[account(mut)]
     pub seller: Signer<'info>,
@@ -27,6 +33,78 @@ pub struct CreateProduct<'info> {
         space = 8 + ProductsList::INIT_SPACE
     )]
     pub product_list: Account<'info, ProductsList>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [
+            b"product_page",
+            seller.key().as_ref(),
+            &product_list.tail_page_index().to_le_bytes()
+        ],
+        bump,
+        space = 8 + ProductPage::INIT_SPACE
+    )]
+    pub product_page: Account<'info, ProductPage>,
+
+    // Marketplace-wide discovery indexes
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [b"category_index", category.index_seed().as_ref()],
+        bump,
+        space = 8 + ProductIndex::INIT_SPACE
+    )]
+    pub category_index: Account<'info, ProductIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [
+            b"category_index",
+            category.index_seed().as_ref(),
+            &category_index.tail_page_index().to_le_bytes()
+        ],
+        bump,
+        space = 8 + ProductIndexPage::INIT_SPACE
+    )]
+    pub category_page: Account<'info, ProductIndexPage>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [b"division_index", division.index_seed().as_ref()],
+        bump,
+        space = 8 + ProductIndex::INIT_SPACE
+    )]
+    pub division_index: Account<'info, ProductIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [
+            b"division_index",
+            division.index_seed().as_ref(),
+            &division_index.tail_page_index().to_le_bytes()
+        ],
+        bump,
+        space = 8 + ProductIndexPage::INIT_SPACE
+    )]
+    pub division_page: Account<'info, ProductIndexPage>,
+
+    #[account(
+        mut,
+        seeds = [b"seller", seller.key().as_ref()],
+        bump = seller_profile.profile_bump,
+        constraint = !seller_profile.suspended @ EcomError::SellerSuspended,
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, MarketplaceConfig>,
     pub system_program: Program<'info, System>,
 }
 
@@ -38,10 +116,16 @@ impl <'info> CreateProduct<'info> {
         price:u32,
         category: Category,
         division:Division,
-        seller_name:String,
         product_imgurl:String,
+        quantity:u32,
+        mint:Option<Pubkey>,
         creation_bump:u8,
     ) -> Result<()> {
+        self.config.require_active(Subsystem::Listings)?;
+        require!(self.config.listings_enabled, EcomError::ListingsDisabled);
+        if let Some(mint) = mint {
+            require!(self.config.accepted_mint(&mint).is_some(), EcomError::MintNotAccepted);
+        }
         let now = Clock::get()?.unix_timestamp;
         let seed_data = [
             self.seller.key().as_ref(),
@@ -59,21 +143,26 @@ impl <'info> CreateProduct<'info> {
             product_name:product_name.clone(), 
             category:category.clone() , 
             division:division.clone(), 
-            quantity: 100, 
-            seller_pubkey:self.seller.key().clone(), 
-            seller_name, 
+            quantity, 
+            seller_pubkey:self.seller.key(), 
             product_short_description, 
             product_imgurl, 
             price, 
-            rating: 0.0, 
-            stock_status: Stock::InStock,
+            mint,
+            rating_sum: 0, 
+            rating_count: 0, 
+            stock_status: if quantity > 0 { Stock::InStock } else { Stock::OutOfStock },
             creation_bump  
         });
+        self.seller_profile.active_listings = self.seller_profile.active_listings
+            .checked_add(1)
+            .ok_or(EcomError::UnexpectedError)?;
         emit!(ProductCreated{
             product_pubkey:self.product.key(),
             seller:self.seller.key(),
             product_name,
             price,
+            mint,
             category,
             division
         });
@@ -82,15 +171,90 @@ impl <'info> CreateProduct<'info> {
     pub fn product_list(
         &mut self,
         product_list_bump: u8,
+        product_page_bump: u8,
     ) -> Result<()> {
-        if self.product_list.products.is_empty() && self.product_list.product_list_bump == 0 {
+        if self.product_list.product_list_bump == 0 {
             self.product_list.set_inner(ProductsList { 
-                products: Vec::new(), 
+                seller: self.seller.key(),
+                page_count: 0,
+                total_products: 0,
                 product_list_bump 
             });
         }
+
+        let page_index = self.product_list.tail_page_index();
+        if page_index == self.product_list.page_count {
+            self.product_page.set_inner(ProductPage {
+                seller: self.seller.key(),
+                page_index,
+                products: Vec::new(),
+                page_bump: product_page_bump,
+            });
+            self.product_list.page_count = page_index
+                .checked_add(1)
+                .ok_or(EcomError::UnexpectedError)?;
+        }
+        self.product_page.products.push(self.product.key());
+        self.product_list.total_products = self.product_list.total_products
+            .checked_add(1)
+            .ok_or(EcomError::UnexpectedError)?;
         Ok(())
     }
-}
 
+    pub fn product_indexes(
+        &mut self,
+        category_index_bump: u8,
+        category_page_bump: u8,
+        division_index_bump: u8,
+        division_page_bump: u8,
+    ) -> Result<()> {
+        let product_key = self.product.key();
+        let payer = self.seller.key();
+        append_to_index(
+            &mut self.category_index,
+            &mut self.category_page,
+            payer,
+            category_index_bump,
+            category_page_bump,
+            product_key,
+        )?;
+        append_to_index(
+            &mut self.division_index,
+            &mut self.division_page,
+            payer,
+            division_index_bump,
+            division_page_bump,
+            product_key,
+        )
+    }
+}
 
+/// Appends `product` to the tail page of a category or division index,
+/// starting a new page, paid for by `payer`, when the tail is full.
+pub(crate) fn append_to_index(
+    index: &mut Account<ProductIndex>,
+    page: &mut Account<ProductIndexPage>,
+    payer: Pubkey,
+    index_bump: u8,
+    page_bump: u8,
+    product: Pubkey,
+) -> Result<()> {
+    index.index_bump = index_bump;
+    let page_index = index.tail_page_index();
+    if page_index == index.page_count {
+        page.set_inner(ProductIndexPage {
+            page_index,
+            payer,
+            products: Vec::new(),
+            page_bump,
+        });
+        index.page_count = page_index
+            .checked_add(1)
+            .ok_or(EcomError::UnexpectedError)?;
+    }
+    page.products.push(product);
+    index.total_products = index.total_products
+        .checked_add(1)
+        .ok_or(EcomError::UnexpectedError)?;
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::{error::EcomError, instructions::{payout_from_vault, release_reserved_stock, release_to_seller}, states::{dispute::{Dispute, DisputeResolved, DisputeStatus, Evidence}, escrow::{Escrow, EscrowStatus}, marketplace::{MarketplaceConfig, Subsystem}, order::Order, payment::{Payment, PaymentStatus}, seller::SellerProfile, vault::VaultState}};
+
+const MAX_EVIDENCE_URI_LEN: usize = 200;
+
+#[derive(Accounts)]
+pub struct OpenDispute<'info>{
+    #[account(
+        mut,
+        constraint = signer.key() == escrow.buyer_pubkey
+            || signer.key() == escrow.seller_pubkey @ EcomError::NotDisputeParty,
+    )]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+
+    #[account(
+        seeds = [
+            b"payment",
+            payment.owner.as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
+    )]
+    pub payment:Account<'info,Payment>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow",payment.key().as_ref()],
+        bump = escrow.escrow_bump,
+    )]
+    pub escrow: Account<'info,Escrow>,
+
+    #[account(
+        init,
+        payer = signer,
+        seeds = [b"dispute",escrow.key().as_ref()],
+        bump,
+        space = 8 + Dispute::INIT_SPACE,
+    )]
+    pub dispute: Account<'info,Dispute>,
+    pub system_program:Program<'info,System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitEvidence<'info>{
+    #[account(
+        mut,
+        constraint = signer.key() == dispute.buyer_pubkey
+            || signer.key() == dispute.seller_pubkey @ EcomError::NotDisputeParty,
+    )]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute",dispute.escrow.as_ref()],
+        bump = dispute.dispute_bump,
+    )]
+    pub dispute: Account<'info,Dispute>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info>{
+    #[account(
+        mut,
+        address = config.arbitrator @ EcomError::InvalidArbitrator,
+    )]
+    pub arbitrator: Signer<'info>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = config.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    ///CHECK: Native SOL Buyer Account, must match the escrow record
+    #[account(
+        mut,
+        address = escrow.buyer_pubkey @ EcomError::InvalidBuyer,
+    )]
+    pub buyer_account: AccountInfo<'info>,
+
+    ///CHECK: Native SOL Seller Account, must match the escrow record
+    #[account(
+        mut,
+        address = escrow.seller_pubkey @ EcomError::InvalidSeller,
+    )]
+    pub seller_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"seller", escrow.seller_pubkey.as_ref()],
+        bump = seller_profile.profile_bump,
+    )]
+    pub seller_profile:Account<'info,SellerProfile>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"payment",
+            payment.owner.as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
+    )]
+    pub payment:Account<'info,Payment>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow",payment.key().as_ref()],
+        bump = escrow.escrow_bump,
+    )]
+    pub escrow: Account<'info,Escrow>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute",escrow.key().as_ref()],
+        bump = dispute.dispute_bump,
+    )]
+    pub dispute: Account<'info,Dispute>,
+
+    // Required when the escrow has an order; its items are restocked if the buyer wins
+    #[account(
+        constraint = escrow.order == Some(order.key()) @ EcomError::OrderMismatch,
+    )]
+    pub order: Option<Account<'info,Order>>,
+
+    #[account(
+        seeds = [b"state", payment.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    // SPL payments only
+    pub mint:Option<Account<'info,Mint>>,
+    #[account(mut)]
+    pub vault_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub buyer_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub seller_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub treasury_token_account:Option<Account<'info,TokenAccount>>,
+    pub token_program:Option<Program<'info,Token>>,
+    pub system_program:Program<'info,System>,
+}
+
+impl <'info> OpenDispute<'info> {
+    pub fn open_dispute(&mut self, dispute_bump:u8) -> Result<()> {
+        self.config.require_active(Subsystem::Escrow)?;
+        require!(self.config.disputes_enabled, EcomError::DisputesDisabled);
+        require!(
+            self.escrow.release_fund
+                && matches!(self.escrow.escrow_status, EscrowStatus::FundsReceived),
+            EcomError::FundsNotFound
+        );
+        let now = Clock::get()?.unix_timestamp;
+
+        self.dispute.set_inner(Dispute {
+            escrow: self.escrow.key(),
+            opened_by: self.signer.key(),
+            buyer_pubkey: self.escrow.buyer_pubkey,
+            seller_pubkey: self.escrow.seller_pubkey,
+            evidence: Vec::new(),
+            dispute_status: DisputeStatus::Open,
+            buyer_share_bps: 0,
+            opened_at: now,
+            resolved_at: 0,
+            dispute_bump,
+        });
+        self.escrow.escrow_status = EscrowStatus::Disputed;
+        self.escrow.update_timestamp = now;
+        Ok(())
+    }
+}
+
+impl <'info> SubmitEvidence<'info> {
+    pub fn submit_evidence(
+        &mut self,
+        content_hash:[u8;32],
+        uri:String,
+    ) -> Result<()> {
+        self.config.require_active(Subsystem::Escrow)?;
+        require!(
+            self.dispute.dispute_status == DisputeStatus::Open,
+            EcomError::DisputeNotOpen
+        );
+        require!(uri.len() <= MAX_EVIDENCE_URI_LEN, EcomError::EvidenceUriTooLong);
+
+        self.dispute.add_evidence(Evidence {
+            submitter: self.signer.key(),
+            content_hash,
+            uri,
+            submitted_at: Clock::get()?.unix_timestamp,
+        })
+    }
+}
+
+impl <'info> ResolveDispute<'info> {
+    pub fn resolve_dispute(
+        &mut self,
+        products:&'info [AccountInfo<'info>],
+        buyer_share_bps:u16,
+    ) -> Result<()> {
+        require!(
+            self.dispute.dispute_status == DisputeStatus::Open
+                && matches!(self.escrow.escrow_status, EscrowStatus::Disputed),
+            EcomError::DisputeNotOpen
+        );
+        require!(
+            u64::from(buyer_share_bps) <= MarketplaceConfig::BPS_DENOMINATOR,
+            EcomError::InvalidSplit
+        );
+        require!(
+            self.escrow.order.is_none() || self.order.is_some(),
+            EcomError::OrderMismatch
+        );
+
+        let amount = self.payment.payment_amount;
+        let buyer_amount = (u128::from(amount) * u128::from(buyer_share_bps)
+            / u128::from(MarketplaceConfig::BPS_DENOMINATOR)) as u64;
+        let seller_amount = amount - buyer_amount;
+
+        if buyer_amount > 0 {
+            payout_from_vault(
+                &self.payment,
+                &self.system_program,
+                &self.vault,
+                &self.vault_state,
+                self.buyer_account.to_account_info(),
+                &self.mint,
+                &self.vault_token_account,
+                &self.buyer_token_account,
+                &self.token_program,
+                buyer_amount,
+            )?;
+        }
+        if seller_amount > 0 {
+            release_to_seller(
+                &mut self.escrow,
+                &mut self.seller_profile,
+                &self.payment,
+                &self.config,
+                &self.system_program,
+                &self.vault,
+                &self.vault_state,
+                self.seller_account.to_account_info(),
+                self.treasury.to_account_info(),
+                &self.mint,
+                &self.vault_token_account,
+                &self.seller_token_account,
+                &self.treasury_token_account,
+                &self.token_program,
+                seller_amount,
+            )?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        self.payment.payment_status = if seller_amount > 0 {
+            PaymentStatus::Success
+        } else {
+            PaymentStatus::Failed
+        };
+        self.escrow.escrow_status = EscrowStatus::Resolved;
+        self.escrow.release_fund = false;
+        self.escrow.update_timestamp = now;
+        self.dispute.dispute_status = DisputeStatus::Resolved;
+        self.dispute.buyer_share_bps = buyer_share_bps;
+        self.dispute.resolved_at = now;
+        // The seller loses when the buyer is awarded the larger share, and
+        // the goods go back on sale
+        if u64::from(buyer_share_bps) * 2 > MarketplaceConfig::BPS_DENOMINATOR {
+            self.seller_profile.record_dispute_lost();
+            release_reserved_stock(&self.payment, self.order.as_deref(), products)?;
+        }
+        emit!(self.seller_profile.reputation());
+
+        emit!(DisputeResolved {
+            dispute: self.dispute.key(),
+            escrow: self.escrow.key(),
+            buyer_amount,
+            seller_amount,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use crate::{error::EcomError, program::EcomDapp, states::{marketplace::{AcceptedMint, MarketplaceConfig, MarketplaceConfigUpdated, PauseStateChanged}, payment::PaymentMethod}};
+
+#[derive(Accounts)]
+pub struct InitializeMarketplace<'info>{
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"marketplace_config"],
+        bump,
+        space = 8 + MarketplaceConfig::INIT_SPACE,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+
+    // Collects platform fees; funded to rent exemption here so small SOL
+    // fees can always be credited to it
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    // Only the program's upgrade authority can set up the marketplace
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ EcomError::Unauthorized,
+    )]
+    pub program: Program<'info,EcomDapp>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ EcomError::Unauthorized,
+    )]
+    pub program_data: Account<'info,ProgramData>,
+    pub system_program:Program<'info,System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info>{
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+        has_one = authority @ EcomError::Unauthorized,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+}
+
+impl <'info> InitializeMarketplace<'info> {
+    pub fn initialize_marketplace(
+        &mut self,
+        arbitrator:Pubkey,
+        fee_bps:u16,
+        config_bump:u8,
+        treasury_bump:u8,
+    ) -> Result<()> {
+        require!(
+            u64::from(fee_bps) <= MarketplaceConfig::BPS_DENOMINATOR,
+            EcomError::InvalidFee
+        );
+        let rent_exempt = Rent::get()?.minimum_balance(0);
+        let shortfall = rent_exempt.saturating_sub(self.treasury.lamports());
+        if shortfall > 0 {
+            let cpi_accounts = Transfer {
+                from: self.authority.to_account_info(),
+                to: self.treasury.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+            transfer(cpi_ctx, shortfall)?;
+        }
+
+        self.config.set_inner(MarketplaceConfig {
+            authority: self.authority.key(),
+            arbitrator,
+            treasury: self.treasury.key(),
+            fee_bps,
+            accepted_mints: Vec::new(),
+            couriers: Vec::new(),
+            listings_enabled: true,
+            spl_payments_enabled: true,
+            cart_checkout_enabled: true,
+            disputes_enabled: true,
+            shipping_window: MarketplaceConfig::DEFAULT_SHIPPING_WINDOW,
+            paused: false,
+            listings_paused: false,
+            payments_paused: false,
+            escrow_paused: false,
+            orders_paused: false,
+            treasury_bump,
+            config_bump,
+        });
+        Ok(())
+    }
+}
+
+impl <'info> UpdateConfig<'info> {
+    pub fn update_config(
+        &mut self,
+        new_authority:Option<Pubkey>,
+        arbitrator:Option<Pubkey>,
+        fee_bps:Option<u16>,
+        listings_enabled:Option<bool>,
+        spl_payments_enabled:Option<bool>,
+        cart_checkout_enabled:Option<bool>,
+        disputes_enabled:Option<bool>,
+        shipping_window:Option<i64>,
+    ) -> Result<()> {
+        let config = &mut self.config;
+        if let Some(new_authority) = new_authority {
+            config.authority = new_authority;
+        }
+        if let Some(arbitrator) = arbitrator {
+            config.arbitrator = arbitrator;
+        }
+        if let Some(fee_bps) = fee_bps {
+            require!(
+                u64::from(fee_bps) <= MarketplaceConfig::BPS_DENOMINATOR,
+                EcomError::InvalidFee
+            );
+            config.fee_bps = fee_bps;
+        }
+        if let Some(listings_enabled) = listings_enabled {
+            config.listings_enabled = listings_enabled;
+        }
+        if let Some(spl_payments_enabled) = spl_payments_enabled {
+            config.spl_payments_enabled = spl_payments_enabled;
+        }
+        if let Some(cart_checkout_enabled) = cart_checkout_enabled {
+            config.cart_checkout_enabled = cart_checkout_enabled;
+        }
+        if let Some(disputes_enabled) = disputes_enabled {
+            config.disputes_enabled = disputes_enabled;
+        }
+        if let Some(shipping_window) = shipping_window {
+            config.set_shipping_window(shipping_window)?;
+        }
+
+        emit!(MarketplaceConfigUpdated {
+            authority: config.authority,
+            arbitrator: config.arbitrator,
+            fee_bps: config.fee_bps,
+            listings_enabled: config.listings_enabled,
+            spl_payments_enabled: config.spl_payments_enabled,
+            cart_checkout_enabled: config.cart_checkout_enabled,
+            disputes_enabled: config.disputes_enabled,
+            shipping_window: config.shipping_window,
+        });
+        Ok(())
+    }
+
+    pub fn set_pause(
+        &mut self,
+        paused:Option<bool>,
+        listings_paused:Option<bool>,
+        payments_paused:Option<bool>,
+        escrow_paused:Option<bool>,
+        orders_paused:Option<bool>,
+    ) -> Result<()> {
+        let config = &mut self.config;
+        if let Some(paused) = paused {
+            config.paused = paused;
+        }
+        if let Some(listings_paused) = listings_paused {
+            config.listings_paused = listings_paused;
+        }
+        if let Some(payments_paused) = payments_paused {
+            config.payments_paused = payments_paused;
+        }
+        if let Some(escrow_paused) = escrow_paused {
+            config.escrow_paused = escrow_paused;
+        }
+        if let Some(orders_paused) = orders_paused {
+            config.orders_paused = orders_paused;
+        }
+
+        emit!(PauseStateChanged {
+            authority: self.authority.key(),
+            paused: config.paused,
+            listings_paused: config.listings_paused,
+            payments_paused: config.payments_paused,
+            escrow_paused: config.escrow_paused,
+            orders_paused: config.orders_paused,
+        });
+        Ok(())
+    }
+
+    pub fn add_accepted_mint(
+        &mut self,
+        mint:Pubkey,
+        payment_method:PaymentMethod,
+    ) -> Result<()> {
+        require!(
+            payment_method != PaymentMethod::SOL,
+            EcomError::InvalidPaymentMethod
+        );
+        let config = &mut self.config;
+        if let Some(accepted) = config.accepted_mints.iter_mut().find(|accepted| accepted.mint == mint) {
+            accepted.payment_method = payment_method;
+            return Ok(());
+        }
+        require!(
+            config.accepted_mints.len() < MarketplaceConfig::MAX_ACCEPTED_MINTS,
+            EcomError::AcceptedMintsFull
+        );
+        config.accepted_mints.push(AcceptedMint { mint, payment_method });
+        Ok(())
+    }
+
+    pub fn add_courier(&mut self, courier:Pubkey) -> Result<()> {
+        let config = &mut self.config;
+        if config.is_approved_courier(&courier) {
+            return Ok(());
+        }
+        require!(
+            config.couriers.len() < MarketplaceConfig::MAX_COURIERS,
+            EcomError::CouriersFull
+        );
+        config.couriers.push(courier);
+        Ok(())
+    }
+
+    pub fn remove_courier(&mut self, courier:Pubkey) -> Result<()> {
+        let config = &mut self.config;
+        let before = config.couriers.len();
+        config.couriers.retain(|approved| *approved != courier);
+        require!(
+            config.couriers.len() < before,
+            EcomError::CourierNotApproved
+        );
+        Ok(())
+    }
+
+    pub fn remove_accepted_mint(&mut self, mint:Pubkey) -> Result<()> {
+        let config = &mut self.config;
+        let before = config.accepted_mints.len();
+        config.accepted_mints.retain(|accepted| accepted.mint != mint);
+        require!(
+            config.accepted_mints.len() < before,
+            EcomError::MintNotAccepted
+        );
+        Ok(())
+    }
+}
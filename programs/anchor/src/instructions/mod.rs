@@ -1,9 +1,27 @@
 pub mod create_product;
+pub mod update_product;
 pub mod add_to_cart;
+pub mod update_cart;
 pub mod create_payment;
 pub mod create_order;
+pub mod checkout_cart;
+pub mod confirm_delivery;
+pub mod refund_escrow;
+pub mod marketplace;
+pub mod dispute;
+pub mod seller;
+pub mod review;
 
 pub use create_product::*;
+pub use update_product::*;
 pub use add_to_cart::*;
+pub use update_cart::*;
 pub use create_payment::*;
 pub use create_order::*;
+pub use checkout_cart::*;
+pub use confirm_delivery::*;
+pub use refund_escrow::*;
+pub use marketplace::*;
+pub use dispute::*;
+pub use seller::*;
+pub use review::*;
@@ -0,0 +1,305 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::{error::EcomError, instructions::payout_from_vault, states::{escrow::{Escrow, EscrowStatus}, order::{Order, OrderStatus, OrderTracking}, payment::{Payment, PaymentStatus}, seller::SellerProfile, vault::VaultState, Product}};
+
+#[derive(Accounts)]
+pub struct RefundEscrow<'info>{
+    #[account(
+        mut,
+        address = escrow.seller_pubkey @ EcomError::InvalidSeller,
+    )]
+    pub seller: Signer<'info>,
+
+    ///CHECK: Native SOL Buyer Account, must match the escrow record
+    #[account(
+        mut,
+        address = escrow.buyer_pubkey @ EcomError::InvalidBuyer,
+    )]
+    pub buyer_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"payment",
+            payment.owner.as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
+    )]
+    pub payment:Account<'info,Payment>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow",payment.key().as_ref()],
+        bump = escrow.escrow_bump,
+    )]
+    pub escrow: Account<'info,Escrow>,
+
+    #[account(
+        seeds = [b"state", payment.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    // Required once an order has been placed for the escrow
+    #[account(
+        mut,
+        seeds = [
+            b"order",
+            order.buyer.as_ref(),
+            &order.order_index.to_le_bytes()
+        ],
+        bump = order.order_bump,
+        constraint = order.escrow == escrow.key()
+            && escrow.order == Some(order.key()) @ EcomError::OrderMismatch,
+    )]
+    pub order: Option<Account<'info,Order>>,
+
+    #[account(
+        mut,
+        seeds = [b"seller", escrow.seller_pubkey.as_ref()],
+        bump = seller_profile.profile_bump,
+    )]
+    pub seller_profile:Account<'info,SellerProfile>,
+
+    // SPL payments only
+    pub mint:Option<Account<'info,Mint>>,
+    #[account(mut)]
+    pub vault_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub buyer_token_account:Option<Account<'info,TokenAccount>>,
+    pub token_program:Option<Program<'info,Token>>,
+    pub system_program:Program<'info,System>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireEscrow<'info>{
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    ///CHECK: Native SOL Buyer Account, must match the escrow record
+    #[account(
+        mut,
+        address = escrow.buyer_pubkey @ EcomError::InvalidBuyer,
+    )]
+    pub buyer_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"payment",
+            payment.owner.as_ref(),
+            &payment.payment_nonce.to_le_bytes()
+        ],
+        bump = payment.payment_bump,
+    )]
+    pub payment:Account<'info,Payment>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow",payment.key().as_ref()],
+        bump = escrow.escrow_bump,
+    )]
+    pub escrow: Account<'info,Escrow>,
+
+    #[account(
+        seeds = [b"state", payment.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+
+    // Required once an order has been placed for the escrow
+    #[account(
+        mut,
+        seeds = [
+            b"order",
+            order.buyer.as_ref(),
+            &order.order_index.to_le_bytes()
+        ],
+        bump = order.order_bump,
+        constraint = order.escrow == escrow.key()
+            && escrow.order == Some(order.key()) @ EcomError::OrderMismatch,
+    )]
+    pub order: Option<Account<'info,Order>>,
+
+    #[account(
+        mut,
+        seeds = [b"seller", escrow.seller_pubkey.as_ref()],
+        bump = seller_profile.profile_bump,
+    )]
+    pub seller_profile:Account<'info,SellerProfile>,
+
+    // SPL payments only
+    pub mint:Option<Account<'info,Mint>>,
+    #[account(mut)]
+    pub vault_token_account:Option<Account<'info,TokenAccount>>,
+    #[account(mut)]
+    pub buyer_token_account:Option<Account<'info,TokenAccount>>,
+    pub token_program:Option<Program<'info,Token>>,
+    pub system_program:Program<'info,System>,
+}
+
+impl <'info> RefundEscrow<'info> {
+    pub fn refund_escrow(&mut self, products:&'info [AccountInfo<'info>]) -> Result<()> {
+        refund_to_buyer(
+            &self.system_program,
+            &self.vault,
+            &self.vault_state,
+            self.buyer_account.to_account_info(),
+            &self.mint,
+            &self.vault_token_account,
+            &self.buyer_token_account,
+            &self.token_program,
+            &mut self.payment,
+            &mut self.escrow,
+            self.order.as_mut(),
+            &mut self.seller_profile,
+            products,
+        )
+    }
+}
+
+impl <'info> ExpireEscrow<'info> {
+    pub fn expire_escrow(&mut self, products:&'info [AccountInfo<'info>]) -> Result<()> {
+        require!(
+            !matches!(&self.order, Some(order) if order.order_tracking >= OrderTracking::Shipped),
+            EcomError::OrderAlreadyShipped
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now > self.escrow.ship_deadline,
+            EcomError::ShippingDeadlineNotReached
+        );
+
+        msg!(
+            "Escrow {} expired at {}, cranked by {}",
+            self.escrow.key(),
+            self.escrow.ship_deadline,
+            self.cranker.key()
+        );
+        refund_to_buyer(
+            &self.system_program,
+            &self.vault,
+            &self.vault_state,
+            self.buyer_account.to_account_info(),
+            &self.mint,
+            &self.vault_token_account,
+            &self.buyer_token_account,
+            &self.token_program,
+            &mut self.payment,
+            &mut self.escrow,
+            self.order.as_mut(),
+            &mut self.seller_profile,
+            products,
+        )
+    }
+}
+
+/// Returns the escrowed funds to the buyer, puts the reserved stock back on
+/// sale, marks the purchase as failed and counts the refund against the seller.
+pub fn refund_to_buyer<'info>(
+    system_program: &Program<'info, System>,
+    vault: &SystemAccount<'info>,
+    vault_state: &Account<'info, VaultState>,
+    buyer_account: AccountInfo<'info>,
+    mint: &Option<Account<'info, Mint>>,
+    vault_token_account: &Option<Account<'info, TokenAccount>>,
+    buyer_token_account: &Option<Account<'info, TokenAccount>>,
+    token_program: &Option<Program<'info, Token>>,
+    payment: &mut Account<'info, Payment>,
+    escrow: &mut Account<'info, Escrow>,
+    order: Option<&mut Account<'info, Order>>,
+    seller_profile: &mut Account<'info, SellerProfile>,
+    products: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let amount = payment.payment_amount;
+    require!(
+        escrow.order.is_none() || order.is_some(),
+        EcomError::OrderMismatch
+    );
+    require!(
+        payment.payment_status == PaymentStatus::Pending,
+        EcomError::InvalidPayment
+    );
+    require!(
+        escrow.release_fund
+            && matches!(escrow.escrow_status, EscrowStatus::FundsReceived),
+        EcomError::FundsNotFound
+    );
+
+    payout_from_vault(
+        payment,
+        system_program,
+        vault,
+        vault_state,
+        buyer_account,
+        mint,
+        vault_token_account,
+        buyer_token_account,
+        token_program,
+        amount,
+    )?;
+    release_reserved_stock(payment, order.as_deref().map(|order| &**order), products)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    payment.payment_status = PaymentStatus::Failed;
+    escrow.escrow_status = EscrowStatus::TransferFailed;
+    escrow.release_fund = false;
+    escrow.update_timestamp = now;
+    if let Some(order) = order {
+        order.order_status = OrderStatus::Returned;
+        order.updated_at = now;
+    }
+    seller_profile.record_refund();
+    emit!(seller_profile.reputation());
+
+    msg!("Refunded {} to buyer {}", amount, escrow.buyer_pubkey);
+    Ok(())
+}
+
+/// Returns the units a purchase reserved to each product's stock. The lines
+/// are the order's items, or the payment's single product when no order was
+/// placed; `products` holds the matching product accounts in line order.
+/// Products delisted since the purchase are skipped.
+pub fn release_reserved_stock<'info>(
+    payment: &Payment,
+    order: Option<&Order>,
+    products: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let lines: Vec<(Pubkey, u32)> = match order {
+        Some(order) => order.items
+            .iter()
+            .map(|item| (item.product, item.quantity))
+            .collect(),
+        None => payment.product_pubkey
+            .map(|product| (product, payment.quantity))
+            .into_iter()
+            .collect(),
+    };
+    require!(products.len() == lines.len(), EcomError::ProductAccountsMismatch);
+
+    for ((product_key, quantity), product_info) in lines.iter().zip(products) {
+        require_keys_eq!(product_info.key(), *product_key, EcomError::ProductAccountsMismatch);
+        if product_info.owner != &crate::ID || product_info.data_is_empty() {
+            continue;
+        }
+        let mut product = Account::<Product>::try_from(product_info)?;
+        product.release_stock(*quantity)?;
+        product.exit(&crate::ID)?;
+    }
+    Ok(())
+}
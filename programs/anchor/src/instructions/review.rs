@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use crate::{error::EcomError, states::{marketplace::{MarketplaceConfig, Subsystem}, order::{Order, OrderTracking}, review::{Review, ReviewSubmitted}, Product}};
+
+#[derive(Accounts)]
+pub struct SubmitReview<'info>{
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [
+            b"order",
+            buyer.key().as_ref(),
+            &order.order_index.to_le_bytes()
+        ],
+        bump = order.order_bump,
+    )]
+    pub order: Account<'info,Order>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"product",
+            product.seller_pubkey.as_ref(),
+            product.product_name.as_bytes()
+        ],
+        bump = product.creation_bump,
+    )]
+    pub product: Account<'info,Product>,
+
+    // One review per product line of an order
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"review", order.key().as_ref(), product.key().as_ref()],
+        bump,
+        space = 8 + Review::INIT_SPACE,
+    )]
+    pub review: Account<'info,Review>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+    pub system_program:Program<'info,System>,
+}
+
+impl <'info> SubmitReview<'info> {
+    pub fn submit_review(
+        &mut self,
+        score:u8,
+        content_hash:[u8;32],
+        uri:String,
+        review_bump:u8,
+    ) -> Result<()> {
+        self.config.require_active(Subsystem::Listings)?;
+        require!(
+            self.order.order_tracking == OrderTracking::Delivered,
+            EcomError::OrderNotDelivered
+        );
+        let product_key = self.product.key();
+        require!(
+            self.order.items.iter().any(|item| item.product == product_key),
+            EcomError::ProductNotInOrder
+        );
+        require!(
+            (Review::MIN_SCORE..=Review::MAX_SCORE).contains(&score),
+            EcomError::InvalidRating
+        );
+        require!(uri.len() <= Review::MAX_URI_LEN, EcomError::ReviewUriTooLong);
+
+        self.product.add_rating(score)?;
+        self.review.set_inner(Review {
+            reviewer: self.buyer.key(),
+            order: self.order.key(),
+            product: product_key,
+            score,
+            content_hash,
+            uri,
+            created_at: Clock::get()?.unix_timestamp,
+            review_bump,
+        });
+
+        emit!(ReviewSubmitted {
+            review: self.review.key(),
+            order: self.order.key(),
+            product: product_key,
+            reviewer: self.buyer.key(),
+            score,
+            rating_sum: self.product.rating_sum,
+            rating_count: self.product.rating_count,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use crate::{error::EcomError, states::{marketplace::{MarketplaceConfig, Subsystem}, seller::{SellerProfile, SellerRegistered, SellerStatusChanged}}};
+
+#[derive(Accounts)]
+pub struct RegisterSeller<'info>{
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        init,
+        payer = seller,
+        seeds = [b"seller", seller.key().as_ref()],
+        bump,
+        space = 8 + SellerProfile::INIT_SPACE,
+    )]
+    pub seller_profile: Account<'info,SellerProfile>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+    pub system_program:Program<'info,System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSellerStatus<'info>{
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+        has_one = authority @ EcomError::Unauthorized,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"seller", seller_profile.seller.as_ref()],
+        bump = seller_profile.profile_bump,
+    )]
+    pub seller_profile: Account<'info,SellerProfile>,
+}
+
+impl <'info> RegisterSeller<'info> {
+    pub fn register_seller(
+        &mut self,
+        display_name:String,
+        contact_hash:[u8;32],
+        logo_uri:String,
+        profile_bump:u8,
+    ) -> Result<()> {
+        self.config.require_active(Subsystem::Listings)?;
+        require!(
+            !display_name.is_empty()
+                && display_name.len() <= SellerProfile::MAX_DISPLAY_NAME_LEN,
+            EcomError::InvalidDisplayName
+        );
+        require!(
+            logo_uri.len() <= SellerProfile::MAX_LOGO_URI_LEN,
+            EcomError::LogoUriTooLong
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        self.seller_profile.set_inner(SellerProfile {
+            seller: self.seller.key(),
+            display_name: display_name.clone(),
+            contact_hash,
+            logo_uri,
+            joined_at: now,
+            verified: false,
+            suspended: false,
+            total_sales: 0,
+            active_listings: 0,
+            orders_completed: 0,
+            refunds: 0,
+            disputes_lost: 0,
+            delivered_orders: 0,
+            total_delivery_time: 0,
+            profile_bump,
+        });
+
+        emit!(SellerRegistered {
+            seller: self.seller.key(),
+            profile: self.seller_profile.key(),
+            display_name,
+            joined_at: now,
+        });
+        Ok(())
+    }
+}
+
+impl <'info> SetSellerStatus<'info> {
+    pub fn set_seller_status(
+        &mut self,
+        verified:Option<bool>,
+        suspended:Option<bool>,
+    ) -> Result<()> {
+        let profile = &mut self.seller_profile;
+        if let Some(verified) = verified {
+            profile.verified = verified;
+        }
+        if let Some(suspended) = suspended {
+            profile.suspended = suspended;
+        }
+
+        emit!(SellerStatusChanged {
+            seller: profile.seller,
+            verified: profile.verified,
+            suspended: profile.suspended,
+            updated_by: self.authority.key(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use crate::{error::EcomError, states::{cart::{Cart, CartList}, marketplace::{MarketplaceConfig, Subsystem}, Product, Stock}};
+
+#[derive(Accounts)]
+pub struct UpdateCartQuantity<'info> {
+    #[account(mut)]
+    pub consumer: Signer<'info>,
+
+    #[account(address = cart.product_pubkey @ EcomError::CartAccountsMismatch)]
+    pub products: Account<'info, Product>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"cart", consumer.key().as_ref(), 
+            cart.product_pubkey.as_ref()
+        ],
+        bump = cart.cart_bump,
+    )]
+    pub cart: Account<'info, Cart>,
+
+    #[account(
+        mut,
+        seeds = [b"cart_list", consumer.key().as_ref()],
+        bump = cart_list.cart_list_bump,
+    )]
+    pub cart_list: Account<'info, CartList>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromCart<'info> {
+    #[account(mut)]
+    pub consumer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = consumer,
+        seeds = [
+            b"cart", consumer.key().as_ref(), 
+            cart.product_pubkey.as_ref()
+        ],
+        bump = cart.cart_bump,
+    )]
+    pub cart: Account<'info, Cart>,
+
+    #[account(
+        mut,
+        seeds = [b"cart_list", consumer.key().as_ref()],
+        bump = cart_list.cart_list_bump,
+    )]
+    pub cart_list: Account<'info, CartList>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClearCart<'info> {
+    #[account(mut)]
+    pub consumer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"cart_list", consumer.key().as_ref()],
+        bump = cart_list.cart_list_bump,
+    )]
+    pub cart_list: Account<'info, CartList>,
+    pub system_program: Program<'info, System>,
+}
+
+impl <'info> UpdateCartQuantity<'info> {
+    pub fn update_cart_quantity(&mut self, quantity: u64) -> Result<()> {
+        self.config.require_active(Subsystem::Orders)?;
+        require!(quantity > 0, EcomError::InvalidQuantity);
+        require!(
+            self.products.stock_status != Stock::OutOfStock
+                && quantity <= self.products.quantity as u64,
+            EcomError::InsufficientStock
+        );
+        self.cart.quantity = quantity;
+        self.cart.record_price(self.products.price as u64);
+        let cart_key = self.cart.key();
+        self.cart_list.upsert(cart_key, self.cart.line_total())
+    }
+}
+
+impl <'info> RemoveFromCart<'info> {
+    pub fn remove_from_cart(&mut self) -> Result<()> {
+        let cart_key = self.cart.key();
+        self.cart_list.remove(&cart_key)?;
+        msg!("Removed {} from cart", cart_key);
+        Ok(())
+    }
+}
+
+impl <'info> ClearCart<'info> {
+    /// `remaining_accounts` holds every writable cart PDA in the cart list.
+    pub fn clear_cart(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(
+            remaining_accounts.len() == self.cart_list.cart_list.len(),
+            EcomError::CartAccountsMismatch
+        );
+        for cart_info in remaining_accounts {
+            require!(
+                self.cart_list.contains(cart_info.key),
+                EcomError::CartAccountsMismatch
+            );
+            let cart = Account::<Cart>::try_from(cart_info)?;
+            cart.close(self.consumer.to_account_info())?;
+        }
+        self.cart_list.clear();
+        Ok(())
+    }
+}
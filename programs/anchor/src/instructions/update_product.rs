@@ -0,0 +1,559 @@
+use anchor_lang::prelude::*;
+use crate::{error::EcomError, instructions::create_product::append_to_index, states::{marketplace::{MarketplaceConfig, Subsystem}, seller::SellerProfile, Category, Division, Product, ProductIndex, ProductIndexPage, ProductPage, ProductUpdated, ProductsList, Stock, remove_from_pages}};
+
+#[derive(Accounts)]
+pub struct UpdateProduct<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"product",
+            seller.key().as_ref(),
+            product.product_name.as_bytes()
+        ],
+        bump = product.creation_bump,
+    )]
+    pub product: Account<'info, Product>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(category: Category)]
+pub struct UpdateProductCategory<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"product",
+            seller.key().as_ref(),
+            product.product_name.as_bytes()
+        ],
+        bump = product.creation_bump,
+    )]
+    pub product: Account<'info, Product>,
+
+    // Index the product currently sits in, laid out as in `DelistProduct`
+    #[account(
+        mut,
+        seeds = [b"category_index", product.category.index_seed().as_ref()],
+        bump = old_index.index_bump,
+    )]
+    pub old_index: Account<'info, ProductIndex>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"category_index",
+            product.category.index_seed().as_ref(),
+            &old_page.page_index.to_le_bytes()
+        ],
+        bump = old_page.page_bump,
+    )]
+    pub old_page: Account<'info, ProductIndexPage>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"category_index",
+            product.category.index_seed().as_ref(),
+            &old_index.last_page_index().to_le_bytes()
+        ],
+        bump = old_last_page.page_bump,
+    )]
+    pub old_last_page: Option<Account<'info, ProductIndexPage>>,
+
+    ///CHECK: Payer of the old index page emptied by the move, must match the page record; only needed when a page closes
+    #[account(mut)]
+    pub old_page_payer: Option<AccountInfo<'info>>,
+
+    // Index the product moves to, created as in `CreateProduct`
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [b"category_index", category.index_seed().as_ref()],
+        bump,
+        space = 8 + ProductIndex::INIT_SPACE
+    )]
+    pub new_index: Account<'info, ProductIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [
+            b"category_index",
+            category.index_seed().as_ref(),
+            &new_index.tail_page_index().to_le_bytes()
+        ],
+        bump,
+        space = 8 + ProductIndexPage::INIT_SPACE
+    )]
+    pub new_page: Account<'info, ProductIndexPage>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(division: Division)]
+pub struct UpdateProductDivision<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"product",
+            seller.key().as_ref(),
+            product.product_name.as_bytes()
+        ],
+        bump = product.creation_bump,
+    )]
+    pub product: Account<'info, Product>,
+
+    #[account(
+        mut,
+        seeds = [b"division_index", product.division.index_seed().as_ref()],
+        bump = old_index.index_bump,
+    )]
+    pub old_index: Account<'info, ProductIndex>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"division_index",
+            product.division.index_seed().as_ref(),
+            &old_page.page_index.to_le_bytes()
+        ],
+        bump = old_page.page_bump,
+    )]
+    pub old_page: Account<'info, ProductIndexPage>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"division_index",
+            product.division.index_seed().as_ref(),
+            &old_index.last_page_index().to_le_bytes()
+        ],
+        bump = old_last_page.page_bump,
+    )]
+    pub old_last_page: Option<Account<'info, ProductIndexPage>>,
+
+    ///CHECK: Payer of the old index page emptied by the move, must match the page record; only needed when a page closes
+    #[account(mut)]
+    pub old_page_payer: Option<AccountInfo<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [b"division_index", division.index_seed().as_ref()],
+        bump,
+        space = 8 + ProductIndex::INIT_SPACE
+    )]
+    pub new_index: Account<'info, ProductIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [
+            b"division_index",
+            division.index_seed().as_ref(),
+            &new_index.tail_page_index().to_le_bytes()
+        ],
+        bump,
+        space = 8 + ProductIndexPage::INIT_SPACE
+    )]
+    pub new_page: Account<'info, ProductIndexPage>,
+
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info,MarketplaceConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DelistProduct<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            b"product",
+            seller.key().as_ref(),
+            product.product_name.as_bytes()
+        ],
+        bump = product.creation_bump,
+    )]
+    pub product: Account<'info, Product>,
+
+    #[account(
+        mut,
+        seeds = [b"product_list", seller.key().as_ref()],
+        bump = product_list.product_list_bump,
+    )]
+    pub product_list: Account<'info, ProductsList>,
+
+    // Catalogue page holding the product
+    #[account(
+        mut,
+        seeds = [
+            b"product_page",
+            seller.key().as_ref(),
+            &product_page.page_index.to_le_bytes()
+        ],
+        bump = product_page.page_bump,
+    )]
+    pub product_page: Account<'info, ProductPage>,
+
+    // Last catalogue page, whose final entry fills the gap; omit when the
+    // product is already on the last page
+    #[account(
+        mut,
+        seeds = [
+            b"product_page",
+            seller.key().as_ref(),
+            &product_list.last_page_index().to_le_bytes()
+        ],
+        bump = last_page.page_bump,
+    )]
+    pub last_page: Option<Account<'info, ProductPage>>,
+
+    // Category and division index pages, laid out like the catalogue pages
+    #[account(
+        mut,
+        seeds = [b"category_index", product.category.index_seed().as_ref()],
+        bump = category_index.index_bump,
+    )]
+    pub category_index: Account<'info, ProductIndex>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"category_index",
+            product.category.index_seed().as_ref(),
+            &category_page.page_index.to_le_bytes()
+        ],
+        bump = category_page.page_bump,
+    )]
+    pub category_page: Account<'info, ProductIndexPage>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"category_index",
+            product.category.index_seed().as_ref(),
+            &category_index.last_page_index().to_le_bytes()
+        ],
+        bump = category_last_page.page_bump,
+    )]
+    pub category_last_page: Option<Account<'info, ProductIndexPage>>,
+
+    ///CHECK: Payer of the category page emptied by the delist, must match the page record; only needed when a page closes
+    #[account(mut)]
+    pub category_page_payer: Option<AccountInfo<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"division_index", product.division.index_seed().as_ref()],
+        bump = division_index.index_bump,
+    )]
+    pub division_index: Account<'info, ProductIndex>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"division_index",
+            product.division.index_seed().as_ref(),
+            &division_page.page_index.to_le_bytes()
+        ],
+        bump = division_page.page_bump,
+    )]
+    pub division_page: Account<'info, ProductIndexPage>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"division_index",
+            product.division.index_seed().as_ref(),
+            &division_index.last_page_index().to_le_bytes()
+        ],
+        bump = division_last_page.page_bump,
+    )]
+    pub division_last_page: Option<Account<'info, ProductIndexPage>>,
+
+    ///CHECK: Payer of the division page emptied by the delist, must match the page record; only needed when a page closes
+    #[account(mut)]
+    pub division_page_payer: Option<AccountInfo<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"seller", seller.key().as_ref()],
+        bump = seller_profile.profile_bump,
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+    pub system_program: Program<'info, System>,
+}
+
+impl <'info> UpdateProduct<'info> {
+    /// Category and division key the global product indexes, so they move
+    /// through `update_product_category` and `update_product_division`.
+    /// A new `quantity` re-derives the stock status before any explicit
+    /// `stock_status` is applied.
+    pub fn update_product(
+        &mut self,
+        product_short_description:Option<String>,
+        price:Option<u32>,
+        product_imgurl:Option<String>,
+        quantity:Option<u32>,
+        stock_status:Option<Stock>,
+    ) -> Result<()> {
+        self.config.require_active(Subsystem::Listings)?;
+        let product = &mut self.product;
+        if let Some(product_short_description) = product_short_description {
+            require!(product_short_description.len() <= 300, EcomError::DescriptionTooLong);
+            product.product_short_description = product_short_description;
+        }
+        if let Some(price) = price {
+            product.price = price;
+        }
+        if let Some(product_imgurl) = product_imgurl {
+            require!(product_imgurl.len() <= 150, EcomError::ImageUrlTooLong);
+            product.product_imgurl = product_imgurl;
+        }
+        if let Some(quantity) = quantity {
+            product.set_quantity(quantity);
+        }
+        if let Some(stock_status) = stock_status {
+            product.set_stock_status(stock_status)?;
+        }
+
+        emit!(ProductUpdated{
+            product_pubkey: product.key(),
+            seller: self.seller.key(),
+            price: product.price,
+            quantity: product.quantity,
+            category: product.category.clone(),
+            division: product.division.clone(),
+            stock_status: product.stock_status.clone(),
+        });
+        Ok(())
+    }
+
+    pub fn restock_product(&mut self, quantity:u32) -> Result<()> {
+        self.config.require_active(Subsystem::Listings)?;
+        let product = &mut self.product;
+        product.restock(quantity)?;
+
+        emit!(ProductUpdated{
+            product_pubkey: product.key(),
+            seller: self.seller.key(),
+            price: product.price,
+            quantity: product.quantity,
+            category: product.category.clone(),
+            division: product.division.clone(),
+            stock_status: product.stock_status.clone(),
+        });
+        Ok(())
+    }
+}
+
+impl <'info> UpdateProductCategory<'info> {
+    /// Moves the product from its current category index to `category`.
+    pub fn update_product_category(
+        &mut self,
+        category:Category,
+        new_index_bump:u8,
+        new_page_bump:u8,
+    ) -> Result<()> {
+        self.config.require_active(Subsystem::Listings)?;
+        require!(
+            category.index_seed() != self.product.category.index_seed(),
+            EcomError::ProductAlreadyIndexed
+        );
+        let product_key = self.product.key();
+        remove_from_index(
+            &mut self.old_index,
+            &mut self.old_page,
+            &mut self.old_last_page,
+            self.old_page_payer.as_ref(),
+            product_key,
+        )?;
+        append_to_index(
+            &mut self.new_index,
+            &mut self.new_page,
+            self.seller.key(),
+            new_index_bump,
+            new_page_bump,
+            product_key,
+        )?;
+        let product = &mut self.product;
+        product.category = category;
+
+        emit!(ProductUpdated{
+            product_pubkey: product_key,
+            seller: self.seller.key(),
+            price: product.price,
+            quantity: product.quantity,
+            category: product.category.clone(),
+            division: product.division.clone(),
+            stock_status: product.stock_status.clone(),
+        });
+        Ok(())
+    }
+}
+
+impl <'info> UpdateProductDivision<'info> {
+    /// Moves the product from its current division index to `division`.
+    pub fn update_product_division(
+        &mut self,
+        division:Division,
+        new_index_bump:u8,
+        new_page_bump:u8,
+    ) -> Result<()> {
+        self.config.require_active(Subsystem::Listings)?;
+        require!(
+            division.index_seed() != self.product.division.index_seed(),
+            EcomError::ProductAlreadyIndexed
+        );
+        let product_key = self.product.key();
+        remove_from_index(
+            &mut self.old_index,
+            &mut self.old_page,
+            &mut self.old_last_page,
+            self.old_page_payer.as_ref(),
+            product_key,
+        )?;
+        append_to_index(
+            &mut self.new_index,
+            &mut self.new_page,
+            self.seller.key(),
+            new_index_bump,
+            new_page_bump,
+            product_key,
+        )?;
+        let product = &mut self.product;
+        product.division = division;
+
+        emit!(ProductUpdated{
+            product_pubkey: product_key,
+            seller: self.seller.key(),
+            price: product.price,
+            quantity: product.quantity,
+            category: product.category.clone(),
+            division: product.division.clone(),
+            stock_status: product.stock_status.clone(),
+        });
+        Ok(())
+    }
+}
+
+impl <'info> DelistProduct<'info> {
+    pub fn delist_product(&mut self) -> Result<()> {
+        let product_key = self.product.key();
+        self.remove_from_catalogue(product_key)?;
+        remove_from_index(
+            &mut self.category_index,
+            &mut self.category_page,
+            &mut self.category_last_page,
+            self.category_page_payer.as_ref(),
+            product_key,
+        )?;
+        remove_from_index(
+            &mut self.division_index,
+            &mut self.division_page,
+            &mut self.division_last_page,
+            self.division_page_payer.as_ref(),
+            product_key,
+        )?;
+        self.seller_profile.active_listings = self.seller_profile.active_listings.saturating_sub(1);
+        msg!("Product Delisted Successfully, {}", product_key);
+        Ok(())
+    }
+
+    /// Takes `product_key` off its page and moves the catalogue's last entry
+    /// into the gap, closing the last page once it is empty.
+    fn remove_from_catalogue(&mut self, product_key: Pubkey) -> Result<()> {
+        let last_index = self.product_list.last_page_index();
+        if self.product_page.page_index == last_index {
+            // A second handle on the same page would overwrite these changes
+            require!(self.last_page.is_none(), EcomError::ProductPageMismatch);
+        } else {
+            require!(self.last_page.is_some(), EcomError::MissingProductPage);
+        }
+
+        let last_page_emptied = remove_from_pages(
+            &mut self.product_page.products,
+            self.last_page.as_mut().map(|page| &mut page.products),
+            &product_key,
+        )?;
+        if last_page_emptied {
+            match self.last_page.as_mut() {
+                Some(last_page) => last_page.close(self.seller.to_account_info())?,
+                None => self.product_page.close(self.seller.to_account_info())?,
+            }
+            self.product_list.page_count = last_index;
+        }
+        self.product_list.total_products = self.product_list.total_products.saturating_sub(1);
+        Ok(())
+    }
+}
+
+/// Index counterpart of `DelistProduct::remove_from_catalogue`. Index pages
+/// are shared by every seller, so an emptied page's rent goes back to the
+/// seller who paid for it rather than to the caller.
+fn remove_from_index<'info>(
+    index: &mut Account<'info, ProductIndex>,
+    page: &mut Account<'info, ProductIndexPage>,
+    last_page: &mut Option<Account<'info, ProductIndexPage>>,
+    page_payer: Option<&AccountInfo<'info>>,
+    product_key: Pubkey,
+) -> Result<()> {
+    let last_index = index.last_page_index();
+    if page.page_index == last_index {
+        require!(last_page.is_none(), EcomError::ProductPageMismatch);
+    } else {
+        require!(last_page.is_some(), EcomError::MissingProductPage);
+    }
+
+    let last_page_emptied = remove_from_pages(
+        &mut page.products,
+        last_page.as_mut().map(|page| &mut page.products),
+        &product_key,
+    )?;
+    if last_page_emptied {
+        match last_page.as_mut() {
+            Some(last_page) => last_page.close(index_page_payer(last_page, page_payer)?)?,
+            None => page.close(index_page_payer(page, page_payer)?)?,
+        }
+        index.page_count = last_index;
+    }
+    index.total_products = index.total_products.saturating_sub(1);
+    Ok(())
+}
+
+fn index_page_payer<'info>(
+    page: &ProductIndexPage,
+    page_payer: Option<&AccountInfo<'info>>,
+) -> Result<AccountInfo<'info>> {
+    page_payer
+        .filter(|payer| payer.key() == page.payer)
+        .cloned()
+        .ok_or(error!(EcomError::IndexPagePayerMismatch))
+}
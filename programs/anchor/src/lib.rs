@@ -1,10 +1,10 @@
-#![allow(unexpected_cfgs,deprecated)]
+#![allow(unexpected_cfgs,deprecated,clippy::too_many_arguments)]
 use anchor_lang::prelude::*;
 mod instructions;
 mod states;
 mod error;
 use crate::instructions::*;
-use crate::states::{Category,Division};
+use crate::states::{Category,Division,Stock,order::OrderTracking,payment::PaymentMethod};
 
 declare_id!("FYo4gi69vTJZJMnNxj2mZz2Q9CbUu12rQDVtHNUFQ2o7");
 
@@ -19,62 +19,141 @@ pub mod ecom_dapp {
         price:u32,
         category: Category,
         division:Division,
-        seller_name:String,
         product_imgurl:String,    
+        quantity:u32,
+        mint:Option<Pubkey>,
     ) -> Result<()> {
         ctx.accounts.create_product(
             product_name, 
             product_short_description, 
             price, category, 
             division, 
-            seller_name, 
             product_imgurl, 
+            quantity,
+            mint,
             ctx.bumps.product,
         )?;
         ctx.accounts.product_list(
             ctx.bumps.product_list,
+            ctx.bumps.product_page,
+        )?;
+        ctx.accounts.product_indexes(
+            ctx.bumps.category_index,
+            ctx.bumps.category_page,
+            ctx.bumps.division_index,
+            ctx.bumps.division_page,
+        )?;
+        Ok(())
+    }
+
+    pub fn update_product(
+        ctx: Context<UpdateProduct>,
+        product_short_description:Option<String>,
+        price:Option<u32>,
+        product_imgurl:Option<String>,
+        quantity:Option<u32>,
+        stock_status:Option<Stock>,
+    ) -> Result<()> {
+        ctx.accounts.update_product(
+            product_short_description,
+            price,
+            product_imgurl,
+            quantity,
+            stock_status,
+        )?;
+        Ok(())
+    }
+
+    pub fn update_product_category(
+        ctx: Context<UpdateProductCategory>,
+        category: Category,
+    ) -> Result<()> {
+        ctx.accounts.update_product_category(
+            category,
+            ctx.bumps.new_index,
+            ctx.bumps.new_page,
         )?;
-        let product_key = ctx.accounts.product.key();
-        ctx.accounts.product_list.products.push(product_key);
+        Ok(())
+    }
+
+    pub fn update_product_division(
+        ctx: Context<UpdateProductDivision>,
+        division: Division,
+    ) -> Result<()> {
+        ctx.accounts.update_product_division(
+            division,
+            ctx.bumps.new_index,
+            ctx.bumps.new_page,
+        )?;
+        Ok(())
+    }
+
+    pub fn restock_product(
+        ctx: Context<UpdateProduct>,
+        quantity:u32,
+    ) -> Result<()> {
+        ctx.accounts.restock_product(quantity)?;
+        Ok(())
+    }
+
+    pub fn delist_product(
+        ctx: Context<DelistProduct>,
+    ) -> Result<()> {
+        ctx.accounts.delist_product()?;
         Ok(())
     }
 
     pub fn add_to_cart(
         ctx: Context<AddToCart>,
-        product_name: String,
         quantity: u32,
-        seller_pubkey: Pubkey,
-        product_imgurl: String,
-        price: u32,
     ) -> Result<()> {
         ctx.accounts.add_to_cart(
-            product_name,
             quantity as u64,
-            seller_pubkey,
-            product_imgurl,
-            price as u64,
             ctx.bumps.cart,
         )?;
 
         ctx.accounts.cart_list(
             ctx.bumps.cart_list,
         )?;
-        let cart_key = ctx.accounts.cart.key();
-        ctx.accounts.cart_list.cart_list.push(cart_key);
+        Ok(())
+    }
+
+    pub fn update_cart_quantity(
+        ctx: Context<UpdateCartQuantity>,
+        quantity: u32,
+    ) -> Result<()> {
+        ctx.accounts.update_cart_quantity(quantity as u64)?;
+        Ok(())
+    }
+
+    pub fn remove_from_cart(
+        ctx: Context<RemoveFromCart>,
+    ) -> Result<()> {
+        ctx.accounts.remove_from_cart()?;
+        Ok(())
+    }
+
+    pub fn clear_cart<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClearCart<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.clear_cart(ctx.remaining_accounts)?;
         Ok(())
     }
 
     pub fn create_payment(
         ctx: Context<CreatePayment>,
+        payment_nonce: u64,
         payment_amount: u64,
-        product_pubkey:Pubkey,
+        quantity:u32,
         tx_signature:Option<String>,
     ) -> Result<()> {
        ctx.accounts.create_payment(
+        payment_nonce,
         payment_amount, 
-        product_pubkey, 
+        quantity, 
         tx_signature, 
         ctx.bumps.payments,
+        ctx.bumps.payment_counter,
     )?;
         Ok(())
     }
@@ -86,13 +165,9 @@ pub mod ecom_dapp {
     }
     pub fn create_escrow(
         ctx: Context<CreateEscrow>,
-        buyer_pubkey:Pubkey,
-        seller_pubkey:Pubkey,
         amount:u64,
     )-> Result<()> {
         ctx.accounts.create_escrow(
-            buyer_pubkey, 
-            seller_pubkey, 
             amount, 
             ctx.bumps.escrow,
             ctx.bumps.vault,
@@ -102,41 +177,242 @@ pub mod ecom_dapp {
     }
     pub fn deposit_escrow(
         ctx: Context<DepositeEscrow>,
-        _product_id: u32,
     ) -> Result<()> {
-        ctx.accounts.deposite_escrow(
-            ctx.bumps.escrow,
-        )?;
+        ctx.accounts.deposite_escrow()?;
         Ok(())
     }
 
     pub fn withdraw_escrow(
         ctx: Context<WithdrawlEscrow>,
-        _product_id: u32,
     ) -> Result<()> {
         ctx.accounts.withdrawl_escrow()?;
         Ok(())
     }
+
+    pub fn confirm_delivery(
+        ctx: Context<ConfirmDelivery>,
+    ) -> Result<()> {
+        ctx.accounts.confirm_delivery()?;
+        Ok(())
+    }
+
+    pub fn refund_escrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundEscrow<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.refund_escrow(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn expire_escrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExpireEscrow<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.expire_escrow(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn submit_review(
+        ctx: Context<SubmitReview>,
+        score:u8,
+        content_hash:[u8;32],
+        uri:String,
+    ) -> Result<()> {
+        ctx.accounts.submit_review(
+            score,
+            content_hash,
+            uri,
+            ctx.bumps.review,
+        )?;
+        Ok(())
+    }
+
+    pub fn initialize_marketplace(
+        ctx: Context<InitializeMarketplace>,
+        arbitrator:Pubkey,
+        fee_bps:u16,
+    ) -> Result<()> {
+        ctx.accounts.initialize_marketplace(
+            arbitrator,
+            fee_bps,
+            ctx.bumps.config,
+            ctx.bumps.treasury,
+        )?;
+        Ok(())
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        new_authority:Option<Pubkey>,
+        arbitrator:Option<Pubkey>,
+        fee_bps:Option<u16>,
+        listings_enabled:Option<bool>,
+        spl_payments_enabled:Option<bool>,
+        cart_checkout_enabled:Option<bool>,
+        disputes_enabled:Option<bool>,
+        shipping_window:Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.update_config(
+            new_authority,
+            arbitrator,
+            fee_bps,
+            listings_enabled,
+            spl_payments_enabled,
+            cart_checkout_enabled,
+            disputes_enabled,
+            shipping_window,
+        )?;
+        Ok(())
+    }
+
+    pub fn set_pause(
+        ctx: Context<UpdateConfig>,
+        paused:Option<bool>,
+        listings_paused:Option<bool>,
+        payments_paused:Option<bool>,
+        escrow_paused:Option<bool>,
+        orders_paused:Option<bool>,
+    ) -> Result<()> {
+        ctx.accounts.set_pause(
+            paused,
+            listings_paused,
+            payments_paused,
+            escrow_paused,
+            orders_paused,
+        )?;
+        Ok(())
+    }
+
+    pub fn register_seller(
+        ctx: Context<RegisterSeller>,
+        display_name:String,
+        contact_hash:[u8;32],
+        logo_uri:String,
+    ) -> Result<()> {
+        ctx.accounts.register_seller(
+            display_name,
+            contact_hash,
+            logo_uri,
+            ctx.bumps.seller_profile,
+        )?;
+        Ok(())
+    }
+
+    pub fn set_seller_status(
+        ctx: Context<SetSellerStatus>,
+        verified:Option<bool>,
+        suspended:Option<bool>,
+    ) -> Result<()> {
+        ctx.accounts.set_seller_status(verified, suspended)?;
+        Ok(())
+    }
+
+    pub fn add_accepted_mint(
+        ctx: Context<UpdateConfig>,
+        mint:Pubkey,
+        payment_method:PaymentMethod,
+    ) -> Result<()> {
+        ctx.accounts.add_accepted_mint(
+            mint,
+            payment_method,
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_accepted_mint(
+        ctx: Context<UpdateConfig>,
+        mint:Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.remove_accepted_mint(mint)?;
+        Ok(())
+    }
+
+    pub fn add_courier(
+        ctx: Context<UpdateConfig>,
+        courier:Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.add_courier(courier)?;
+        Ok(())
+    }
+
+    pub fn remove_courier(
+        ctx: Context<UpdateConfig>,
+        courier:Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.remove_courier(courier)?;
+        Ok(())
+    }
+
+    pub fn open_dispute(
+        ctx: Context<OpenDispute>,
+    ) -> Result<()> {
+        ctx.accounts.open_dispute(
+            ctx.bumps.dispute,
+        )?;
+        Ok(())
+    }
+
+    pub fn submit_evidence(
+        ctx: Context<SubmitEvidence>,
+        content_hash:[u8;32],
+        uri:String,
+    ) -> Result<()> {
+        ctx.accounts.submit_evidence(
+            content_hash,
+            uri,
+        )?;
+        Ok(())
+    }
+
+    pub fn resolve_dispute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveDispute<'info>>,
+        buyer_share_bps:u16,
+    ) -> Result<()> {
+        ctx.accounts.resolve_dispute(
+            ctx.remaining_accounts,
+            buyer_share_bps,
+        )?;
+        Ok(())
+    }
     pub fn create_order(
         ctx: Context<CreateOrder>,
-        payment_id:String,
     )->Result<()> {
         ctx.accounts.create_order(
-            payment_id,
-            ctx.bumps.order
+            ctx.bumps.order,
+            ctx.bumps.order_counter,
+        )?;
+        Ok(())
+    }
+
+    pub fn checkout_cart<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CheckoutCart<'info>>,
+        payment_nonce:u64,
+        expected_total:u64,
+    )->Result<()> {
+        ctx.accounts.checkout_cart(
+            ctx.remaining_accounts,
+            payment_nonce,
+            expected_total,
+            &ctx.bumps,
         )?;
         Ok(())
     }
 
     pub fn update_order(
         ctx: Context<UpdateOrder>,
-        orderstatus_str:String
+        order_tracking:OrderTracking,
     )->Result<()> {
         ctx.accounts.update_tracking_status(
-            orderstatus_str,
+            order_tracking,
         )?;
         Ok(())
     }
+    pub fn assign_courier(
+        ctx: Context<AssignCourier>,
+        courier:Option<Pubkey>,
+    )->Result<()> {
+        ctx.accounts.assign_courier(courier)?;
+        Ok(())
+    }
+
     pub fn close_order(
         ctx:Context<CloseOrder>
     ) ->Result<()> {
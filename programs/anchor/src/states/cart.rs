@@ -1,9 +1,11 @@
 use anchor_lang::prelude::*;
+use crate::{error::EcomError, states::{order::Order, product::Stock}};
 
 #[account]
 #[derive(InitSpace)]
 pub struct Cart{
     pub product_id: [u8;16],
+    pub product_pubkey: Pubkey,
     #[max_len(50)]
     pub product_name: String,
     pub quantity: u64,
@@ -16,14 +18,110 @@ pub struct Cart{
     pub cart_bump:u8,
 }
 
+impl Cart {
+    pub const MAX_PRICE_HISTORY: usize = 40;
+
+    /// Records the product's current unit price, keeping only the latest
+    /// `MAX_PRICE_HISTORY` prices.
+    pub fn record_price(&mut self, price: u64) {
+        if self.amount.last() == Some(&price) {
+            return;
+        }
+        if self.amount.len() >= Self::MAX_PRICE_HISTORY {
+            self.amount.remove(0);
+        }
+        self.amount.push(price);
+    }
+
+    /// Latest unit price times quantity.
+    pub fn line_total(&self) -> u64 {
+        self.amount
+            .last()
+            .copied()
+            .unwrap_or_default()
+            .saturating_mul(self.quantity)
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct CartList{
     #[max_len(40)]
-    pub cart_list: Vec<Pubkey>,
+    pub cart_list: Vec<CartEntry>,
     pub total_amount:u64, 
     pub cart_list_bump:u8,
 }
+
+#[derive(Clone,AnchorDeserialize,AnchorSerialize,InitSpace,PartialEq,Eq)]
+pub struct CartEntry{
+    pub cart: Pubkey,
+    pub line_total: u64,
+}
+
+impl CartList {
+    pub const MAX_ENTRIES: usize = 40;
+
+    pub fn contains(&self, cart: &Pubkey) -> bool {
+        self.cart_list.iter().any(|entry| entry.cart == *cart)
+    }
+
+    pub fn cart_keys(&self) -> Vec<Pubkey> {
+        self.cart_list.iter().map(|entry| entry.cart).collect()
+    }
+
+    /// Cart keys to check out, validated against the keys of the (cart,
+    /// product) account pairs passed with the checkout: one pair per entry,
+    /// in cart list order, with no cart repeated.
+    pub fn checkout_keys(&self, account_keys: &[Pubkey]) -> Result<Vec<Pubkey>> {
+        let cart_keys = self.cart_keys();
+        require!(!cart_keys.is_empty(), EcomError::EmptyCart);
+        require!(cart_keys.len() <= Order::MAX_ITEMS, EcomError::TooManyCartItems);
+        require!(
+            account_keys.len() == cart_keys.len() * 2,
+            EcomError::CartAccountsMismatch
+        );
+        for (i, key) in cart_keys.iter().enumerate() {
+            require!(
+                !cart_keys[..i].contains(key) && account_keys[i * 2] == *key,
+                EcomError::CartAccountsMismatch
+            );
+        }
+        Ok(cart_keys)
+    }
+
+    /// Records `cart` at `line_total`, replacing the existing entry on re-add.
+    pub fn upsert(&mut self, cart: Pubkey, line_total: u64) -> Result<()> {
+        match self.cart_list.iter_mut().find(|entry| entry.cart == cart) {
+            Some(entry) => entry.line_total = line_total,
+            None => {
+                require!(
+                    self.cart_list.len() < Self::MAX_ENTRIES,
+                    EcomError::CartFull
+                );
+                self.cart_list.push(CartEntry { cart, line_total });
+            }
+        }
+        self.recompute_total()
+    }
+
+    pub fn remove(&mut self, cart: &Pubkey) -> Result<()> {
+        self.cart_list.retain(|entry| entry.cart != *cart);
+        self.recompute_total()
+    }
+
+    pub fn clear(&mut self) {
+        self.cart_list.clear();
+        self.total_amount = 0;
+    }
+
+    fn recompute_total(&mut self) -> Result<()> {
+        self.total_amount = self.cart_list
+            .iter()
+            .try_fold(0u64, |total, entry| total.checked_add(entry.line_total))
+            .ok_or(EcomError::PriceMismatch)?;
+        Ok(())
+    }
+}
 #[event]
 pub struct CartCreated {
     pub seller: Pubkey,
@@ -31,9 +129,148 @@ pub struct CartCreated {
     pub product_name: String,
     pub amount: u64,
 }
-#[derive(AnchorDeserialize,AnchorSerialize,Clone,InitSpace, PartialEq, Eq)]
-pub enum Stock {
-    OutOfStock,
-    InStock,
-    Restoring
-}
\ No newline at end of file
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_list() -> CartList {
+        CartList {
+            cart_list: Vec::new(),
+            total_amount: 0,
+            cart_list_bump: 255,
+        }
+    }
+
+    #[test]
+    fn total_is_sum_of_line_totals() {
+        let mut list = empty_list();
+        list.upsert(Pubkey::new_unique(), 100 * 2).unwrap();
+        list.upsert(Pubkey::new_unique(), 50 * 3).unwrap();
+        assert_eq!(list.total_amount, 350);
+        assert_eq!(list.cart_list.len(), 2);
+    }
+
+    #[test]
+    fn later_quantity_does_not_scale_running_total() {
+        let mut list = empty_list();
+        list.upsert(Pubkey::new_unique(), 100).unwrap();
+        list.upsert(Pubkey::new_unique(), 10 * 5).unwrap();
+        assert_eq!(list.total_amount, 150);
+    }
+
+    #[test]
+    fn re_adding_a_cart_updates_instead_of_appending() {
+        let mut list = empty_list();
+        let cart = Pubkey::new_unique();
+        list.upsert(cart, 100).unwrap();
+        list.upsert(Pubkey::new_unique(), 40).unwrap();
+        list.upsert(cart, 300).unwrap();
+        assert_eq!(list.cart_list.len(), 2);
+        assert_eq!(list.total_amount, 340);
+        assert_eq!(list.cart_keys().iter().filter(|key| **key == cart).count(), 1);
+    }
+
+    #[test]
+    fn remove_and_clear_keep_total_consistent() {
+        let mut list = empty_list();
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        list.upsert(first, 120).unwrap();
+        list.upsert(second, 80).unwrap();
+
+        list.remove(&first).unwrap();
+        assert_eq!(list.total_amount, 80);
+        assert!(!list.contains(&first));
+
+        list.clear();
+        assert_eq!(list.total_amount, 0);
+        assert!(list.cart_list.is_empty());
+    }
+
+    #[test]
+    fn rejects_entries_beyond_capacity() {
+        let mut list = empty_list();
+        for _ in 0..CartList::MAX_ENTRIES {
+            list.upsert(Pubkey::new_unique(), 1).unwrap();
+        }
+        assert!(list.upsert(Pubkey::new_unique(), 1).is_err());
+        assert_eq!(list.total_amount, CartList::MAX_ENTRIES as u64);
+    }
+
+    #[test]
+    fn checkout_takes_one_cart_product_pair_per_entry() {
+        let mut list = empty_list();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (first_product, second_product) = (Pubkey::new_unique(), Pubkey::new_unique());
+        list.upsert(first, 100).unwrap();
+        list.upsert(second, 40).unwrap();
+
+        let keys = list
+            .checkout_keys(&[first, first_product, second, second_product])
+            .unwrap();
+        assert_eq!(keys, vec![first, second]);
+        assert!(empty_list().checkout_keys(&[]).is_err());
+    }
+
+    #[test]
+    fn checkout_rejects_mismatched_or_duplicated_accounts() {
+        let mut list = empty_list();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let product = Pubkey::new_unique();
+        list.upsert(first, 100).unwrap();
+        list.upsert(second, 40).unwrap();
+
+        // Missing pair, pairs out of order, a cart passed twice
+        assert!(list.checkout_keys(&[first, product]).is_err());
+        assert!(list.checkout_keys(&[second, product, first, product]).is_err());
+        assert!(list.checkout_keys(&[first, product, first, product]).is_err());
+
+        // A cart list holding the same cart twice is rejected as well
+        list.cart_list.push(CartEntry { cart: first, line_total: 100 });
+        assert!(list
+            .checkout_keys(&[first, product, second, product, first, product])
+            .is_err());
+    }
+
+    #[test]
+    fn line_total_uses_latest_price() {
+        let cart = Cart {
+            product_id: [0; 16],
+            product_pubkey: Pubkey::new_unique(),
+            product_name: String::new(),
+            quantity: 3,
+            seller_pubkey: Pubkey::new_unique(),
+            product_imgurl: String::new(),
+            stock_status: Stock::InStock,
+            amount: vec![10, 25],
+            cart_bump: 255,
+        };
+        assert_eq!(cart.line_total(), 75);
+    }
+
+    #[test]
+    fn record_price_tracks_current_price() {
+        let mut cart = Cart {
+            product_id: [0; 16],
+            product_pubkey: Pubkey::new_unique(),
+            product_name: String::new(),
+            quantity: 2,
+            seller_pubkey: Pubkey::new_unique(),
+            product_imgurl: String::new(),
+            stock_status: Stock::InStock,
+            amount: vec![10],
+            cart_bump: 255,
+        };
+        cart.record_price(10);
+        cart.record_price(12);
+        assert_eq!(cart.amount, vec![10, 12]);
+        assert_eq!(cart.line_total(), 24);
+
+        for price in 0..Cart::MAX_PRICE_HISTORY as u64 {
+            cart.record_price(100 + price);
+        }
+        assert_eq!(cart.amount.len(), Cart::MAX_PRICE_HISTORY);
+        assert_eq!(cart.amount.last(), Some(&(100 + Cart::MAX_PRICE_HISTORY as u64 - 1)));
+    }
+}
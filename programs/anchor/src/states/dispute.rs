@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use crate::error::EcomError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Dispute{
+    pub escrow:Pubkey,
+    pub opened_by:Pubkey,
+    pub buyer_pubkey:Pubkey,
+    pub seller_pubkey:Pubkey,
+    #[max_len(10)]
+    pub evidence:Vec<Evidence>,
+    pub dispute_status:DisputeStatus,
+    pub buyer_share_bps:u16,
+    pub opened_at:i64,
+    pub resolved_at:i64,
+    pub dispute_bump:u8,
+}
+
+impl Dispute {
+    /// Each party gets its own share of the evidence slots, so neither can
+    /// fill them all and lock the other out.
+    pub const MAX_EVIDENCE_PER_PARTY: usize = 5;
+
+    pub fn add_evidence(&mut self, evidence: Evidence) -> Result<()> {
+        let submitted = self.evidence
+            .iter()
+            .filter(|existing| existing.submitter == evidence.submitter)
+            .count();
+        require!(
+            submitted < Self::MAX_EVIDENCE_PER_PARTY,
+            EcomError::EvidenceLimitReached
+        );
+        self.evidence.push(evidence);
+        Ok(())
+    }
+}
+
+#[derive(Clone,AnchorDeserialize,AnchorSerialize,InitSpace)]
+pub struct Evidence{
+    pub submitter:Pubkey,
+    pub content_hash:[u8;32],
+    #[max_len(200)]
+    pub uri:String,
+    pub submitted_at:i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub dispute: Pubkey,
+    pub escrow: Pubkey,
+    pub buyer_amount: u64,
+    pub seller_amount: u64,
+}
+
+#[derive(Clone,AnchorDeserialize,AnchorSerialize,InitSpace,PartialEq,Eq)]
+pub enum DisputeStatus {
+    Open,
+    Resolved,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evidence(submitter: Pubkey) -> Evidence {
+        Evidence {
+            submitter,
+            content_hash: [0; 32],
+            uri: String::new(),
+            submitted_at: 0,
+        }
+    }
+
+    #[test]
+    fn evidence_is_capped_per_party() {
+        let (buyer, seller) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut dispute = Dispute {
+            escrow: Pubkey::default(),
+            opened_by: buyer,
+            buyer_pubkey: buyer,
+            seller_pubkey: seller,
+            evidence: Vec::new(),
+            dispute_status: DisputeStatus::Open,
+            buyer_share_bps: 0,
+            opened_at: 0,
+            resolved_at: 0,
+            dispute_bump: 0,
+        };
+        for _ in 0..Dispute::MAX_EVIDENCE_PER_PARTY {
+            dispute.add_evidence(evidence(buyer)).unwrap();
+        }
+        assert!(dispute.add_evidence(evidence(buyer)).is_err());
+
+        for _ in 0..Dispute::MAX_EVIDENCE_PER_PARTY {
+            dispute.add_evidence(evidence(seller)).unwrap();
+        }
+        assert!(dispute.add_evidence(evidence(seller)).is_err());
+        assert_eq!(dispute.evidence.len(), 2 * Dispute::MAX_EVIDENCE_PER_PARTY);
+    }
+}
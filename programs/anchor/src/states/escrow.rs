@@ -4,20 +4,92 @@ use anchor_lang::prelude::*;
 #[derive(InitSpace)]
 pub struct Escrow{
     pub owner:Pubkey,
+    pub payment:Pubkey,
+    pub order:Option<Pubkey>,
     pub buyer_pubkey:Pubkey,
     pub seller_pubkey:Pubkey,
     pub amount:u64,
     pub release_fund:bool,
     pub time_stamp:i64,
     pub update_timestamp:i64,
+    pub ship_deadline:i64,
+    pub seller_payout:u64,
+    pub platform_fee:u64,
     pub escrow_status:EscrowStatus,
     pub escrow_bump:u8,
 }
 
+impl Escrow {
+    /// The escrowed funds have left the vault for good: released to the
+    /// seller, refunded to the buyer or split by the arbitrator.
+    pub fn is_settled(&self) -> bool {
+        !self.release_fund
+            && matches!(
+                self.escrow_status,
+                EscrowStatus::SwapSuccess | EscrowStatus::TransferFailed | EscrowStatus::Resolved
+            )
+    }
+
+    /// Nothing is held for either party: the escrow is settled or was never
+    /// funded.
+    pub fn is_closable(&self) -> bool {
+        self.is_settled()
+            || (!self.release_fund && matches!(self.escrow_status, EscrowStatus::SwapPending))
+    }
+}
+
 #[derive(Clone,AnchorDeserialize,AnchorSerialize,InitSpace)]
 pub enum EscrowStatus {
     SwapPending,
     FundsReceived,
     SwapSuccess,
     TransferFailed,
-}
\ No newline at end of file
+    Disputed,
+    Resolved,
+}
+
+#[event]
+pub struct FundsReleased {
+    pub escrow: Pubkey,
+    pub seller: Pubkey,
+    pub treasury: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub seller_payout: u64,
+    pub platform_fee: u64,
+    pub fee_bps: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escrow(escrow_status: EscrowStatus, release_fund: bool) -> Escrow {
+        Escrow {
+            owner: Pubkey::default(),
+            payment: Pubkey::default(),
+            order: None,
+            buyer_pubkey: Pubkey::default(),
+            seller_pubkey: Pubkey::default(),
+            amount: 0,
+            release_fund,
+            time_stamp: 0,
+            update_timestamp: 0,
+            ship_deadline: 0,
+            seller_payout: 0,
+            platform_fee: 0,
+            escrow_status,
+            escrow_bump: 0,
+        }
+    }
+
+    #[test]
+    fn held_funds_block_closing() {
+        assert!(!escrow(EscrowStatus::FundsReceived, true).is_closable());
+        assert!(!escrow(EscrowStatus::Disputed, true).is_closable());
+        assert!(escrow(EscrowStatus::SwapPending, false).is_closable());
+        assert!(!escrow(EscrowStatus::SwapPending, false).is_settled());
+        assert!(escrow(EscrowStatus::SwapSuccess, false).is_settled());
+        assert!(escrow(EscrowStatus::TransferFailed, false).is_settled());
+        assert!(escrow(EscrowStatus::Resolved, false).is_settled());
+    }
+}
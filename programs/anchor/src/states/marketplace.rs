@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+use crate::{error::EcomError, states::payment::PaymentMethod};
+
+#[account]
+#[derive(InitSpace)]
+pub struct MarketplaceConfig{
+    pub authority:Pubkey,
+    pub arbitrator:Pubkey,
+    pub treasury:Pubkey,
+    pub fee_bps:u16,
+    #[max_len(5)]
+    pub accepted_mints:Vec<AcceptedMint>,
+    // Delivery couriers vetted by the authority
+    #[max_len(10)]
+    pub couriers:Vec<Pubkey>,
+    pub listings_enabled:bool,
+    pub spl_payments_enabled:bool,
+    pub cart_checkout_enabled:bool,
+    pub disputes_enabled:bool,
+    // Seconds a seller has to ship once an escrow is funded
+    pub shipping_window:i64,
+    // Emergency switches; refunds and closes stay available while paused
+    pub paused:bool,
+    pub listings_paused:bool,
+    pub payments_paused:bool,
+    pub escrow_paused:bool,
+    pub orders_paused:bool,
+    pub treasury_bump:u8,
+    pub config_bump:u8,
+}
+
+#[derive(Clone,AnchorDeserialize,AnchorSerialize,InitSpace)]
+pub struct AcceptedMint{
+    pub mint:Pubkey,
+    pub payment_method:PaymentMethod,
+}
+
+#[event]
+pub struct MarketplaceConfigUpdated {
+    pub authority: Pubkey,
+    pub arbitrator: Pubkey,
+    pub fee_bps: u16,
+    pub listings_enabled: bool,
+    pub spl_payments_enabled: bool,
+    pub cart_checkout_enabled: bool,
+    pub disputes_enabled: bool,
+    pub shipping_window: i64,
+}
+
+/// Areas of the program that can be paused independently.
+#[derive(Clone,Copy,PartialEq,Eq,AnchorDeserialize,AnchorSerialize)]
+pub enum Subsystem {
+    Listings,
+    Payments,
+    Escrow,
+    Orders,
+}
+
+#[event]
+pub struct PauseStateChanged {
+    pub authority: Pubkey,
+    pub paused: bool,
+    pub listings_paused: bool,
+    pub payments_paused: bool,
+    pub escrow_paused: bool,
+    pub orders_paused: bool,
+}
+
+impl MarketplaceConfig {
+    pub const MAX_ACCEPTED_MINTS: usize = 5;
+    pub const BPS_DENOMINATOR: u64 = 10_000;
+    pub const MAX_COURIERS: usize = 10;
+    pub const DEFAULT_SHIPPING_WINDOW: i64 = 7 * 24 * 60 * 60;
+    pub const MIN_SHIPPING_WINDOW: i64 = 24 * 60 * 60;
+
+    /// Splits a released `amount` into `(seller_payout, platform_fee)`.
+    pub fn split_fee(&self, amount: u64) -> (u64, u64) {
+        let fee = (u128::from(amount) * u128::from(self.fee_bps)
+            / u128::from(Self::BPS_DENOMINATOR)) as u64;
+        (amount - fee, fee)
+    }
+
+    pub fn is_paused(&self, subsystem: Subsystem) -> bool {
+        self.paused
+            || match subsystem {
+                Subsystem::Listings => self.listings_paused,
+                Subsystem::Payments => self.payments_paused,
+                Subsystem::Escrow => self.escrow_paused,
+                Subsystem::Orders => self.orders_paused,
+            }
+    }
+
+    pub fn require_active(&self, subsystem: Subsystem) -> Result<()> {
+        require!(!self.paused, EcomError::ProgramPaused);
+        require!(!self.is_paused(subsystem), EcomError::SubsystemPaused);
+        Ok(())
+    }
+
+    pub fn is_approved_courier(&self, courier: &Pubkey) -> bool {
+        self.couriers.contains(courier)
+    }
+
+    pub fn accepted_mint(&self, mint: &Pubkey) -> Option<&AcceptedMint> {
+        self.accepted_mints.iter().find(|accepted| accepted.mint == *mint)
+    }
+
+    pub fn set_shipping_window(&mut self, shipping_window: i64) -> Result<()> {
+        require!(
+            shipping_window >= Self::MIN_SHIPPING_WINDOW,
+            EcomError::InvalidShippingWindow
+        );
+        self.shipping_window = shipping_window;
+        Ok(())
+    }
+
+    /// Shipping deadline for an escrow funded at `funded_at`.
+    pub fn ship_deadline(&self, funded_at: i64) -> Result<i64> {
+        funded_at
+            .checked_add(self.shipping_window)
+            .ok_or(error!(EcomError::InvalidShippingWindow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(fee_bps: u16) -> MarketplaceConfig {
+        MarketplaceConfig {
+            authority: Pubkey::default(),
+            arbitrator: Pubkey::default(),
+            treasury: Pubkey::default(),
+            fee_bps,
+            accepted_mints: Vec::new(),
+            couriers: Vec::new(),
+            listings_enabled: true,
+            spl_payments_enabled: true,
+            cart_checkout_enabled: true,
+            disputes_enabled: true,
+            shipping_window: MarketplaceConfig::DEFAULT_SHIPPING_WINDOW,
+            paused: false,
+            listings_paused: false,
+            payments_paused: false,
+            escrow_paused: false,
+            orders_paused: false,
+            treasury_bump: 0,
+            config_bump: 0,
+        }
+    }
+
+    #[test]
+    fn split_fee_rounds_fee_down() {
+        assert_eq!(config(250).split_fee(1_000_001), (975_001, 25_000));
+        assert_eq!(config(250).split_fee(39), (39, 0));
+    }
+
+    #[test]
+    fn shipping_window_has_a_floor() {
+        let mut config = config(0);
+        assert!(config
+            .set_shipping_window(MarketplaceConfig::MIN_SHIPPING_WINDOW - 1)
+            .is_err());
+        assert_eq!(config.shipping_window, MarketplaceConfig::DEFAULT_SHIPPING_WINDOW);
+
+        config.set_shipping_window(MarketplaceConfig::MIN_SHIPPING_WINDOW).unwrap();
+        assert_eq!(config.ship_deadline(100).unwrap(), 100 + MarketplaceConfig::MIN_SHIPPING_WINDOW);
+    }
+
+    #[test]
+    fn split_fee_handles_bounds() {
+        assert_eq!(config(0).split_fee(500), (500, 0));
+        assert_eq!(config(10_000).split_fee(500), (0, 500));
+        assert_eq!(config(10_000).split_fee(u64::MAX), (0, u64::MAX));
+    }
+
+    #[test]
+    fn global_pause_covers_every_subsystem() {
+        let mut config = config(0);
+        config.escrow_paused = true;
+        assert!(config.is_paused(Subsystem::Escrow));
+        assert!(!config.is_paused(Subsystem::Listings));
+
+        config.paused = true;
+        for subsystem in [
+            Subsystem::Listings,
+            Subsystem::Payments,
+            Subsystem::Escrow,
+            Subsystem::Orders,
+        ] {
+            assert!(config.is_paused(subsystem));
+        }
+    }
+}
@@ -4,5 +4,9 @@ pub mod payment;
 pub mod order;
 pub mod escrow;
 pub mod vault;
+pub mod marketplace;
+pub mod dispute;
+pub mod seller;
+pub mod review;
 
 pub use product::*;
\ No newline at end of file
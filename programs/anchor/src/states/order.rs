@@ -4,8 +4,14 @@ use anchor_lang::prelude::*;
 #[derive(InitSpace)]
 pub struct Order{
     pub order_id:[u8;16],
-    #[max_len(64)]
-    pub payment_id:String,
+    pub buyer:Pubkey,
+    pub seller:Pubkey,
+    pub courier:Option<Pubkey>,
+    pub order_index:u64,
+    pub payment:Pubkey,
+    pub escrow:Pubkey,
+    #[max_len(10)]
+    pub items:Vec<OrderItem>,
     pub tracking_id:[u8;16],
     pub order_status:OrderStatus,
     pub order_tracking:OrderTracking,
@@ -14,6 +20,53 @@ pub struct Order{
     pub order_bump:u8,
 }
 
+#[derive(Clone,AnchorDeserialize,AnchorSerialize,InitSpace)]
+pub struct OrderItem{
+    pub product:Pubkey,
+    pub quantity:u32,
+    pub price:u32,
+}
+
+impl Order {
+    pub const MAX_ITEMS: usize = 10;
+
+    pub fn is_courier(&self, key: &Pubkey) -> bool {
+        self.courier.as_ref() == Some(key)
+    }
+
+    /// Sellers and couriers move the parcel along; only the buyer or the
+    /// courier can attest that it was delivered. Couriers are allowlisted in
+    /// `MarketplaceConfig` and are never the buyer or seller.
+    pub fn can_set_tracking(&self, key: &Pubkey, tracking: &OrderTracking) -> bool {
+        match tracking {
+            OrderTracking::Delivered => self.buyer == *key || self.is_courier(key),
+            _ => self.seller == *key || self.is_courier(key),
+        }
+    }
+
+    /// The courier delivers from `OutForDelivery` like any other step, but
+    /// the buyer may confirm receipt from `Shipped` onward so a seller who
+    /// stops updating the tracking cannot hold up the payout.
+    pub fn can_confirm_delivery(&self, key: &Pubkey) -> bool {
+        if self.buyer == *key {
+            matches!(
+                self.order_tracking,
+                OrderTracking::Shipped | OrderTracking::OutForDelivery
+            )
+        } else {
+            self.order_tracking.can_transition_to(&OrderTracking::Delivered)
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct OrderCounter{
+    pub buyer:Pubkey,
+    pub order_count:u64,
+    pub counter_bump:u8,
+}
+
 #[derive(Clone,AnchorDeserialize,AnchorSerialize,InitSpace)]
 pub enum OrderStatus {
     Pending,
@@ -21,7 +74,7 @@ pub enum OrderStatus {
     Failed,
     Returned
 }
-#[derive(Clone,AnchorDeserialize,AnchorSerialize,InitSpace)]
+#[derive(Clone,AnchorDeserialize,AnchorSerialize,InitSpace,PartialEq,Eq,PartialOrd,Ord)]
 pub enum OrderTracking {
     WatingForOrders,
     Booked,
@@ -29,4 +82,73 @@ pub enum OrderTracking {
     Shipped,
     OutForDelivery,
     Delivered,
-}
\ No newline at end of file
+}
+
+impl OrderTracking {
+    /// The only status an order may move to from this one.
+    pub fn next(&self) -> Option<OrderTracking> {
+        match self {
+            OrderTracking::WatingForOrders => Some(OrderTracking::Booked),
+            OrderTracking::Booked => Some(OrderTracking::InTransit),
+            OrderTracking::InTransit => Some(OrderTracking::Shipped),
+            OrderTracking::Shipped => Some(OrderTracking::OutForDelivery),
+            OrderTracking::OutForDelivery => Some(OrderTracking::Delivered),
+            OrderTracking::Delivered => None,
+        }
+    }
+
+    pub fn can_transition_to(&self, new_status: &OrderTracking) -> bool {
+        self.next().as_ref() == Some(new_status)
+    }
+}
+
+#[event]
+pub struct OrderStatusChanged {
+    pub order: Pubkey,
+    pub old_status: OrderTracking,
+    pub new_status: OrderTracking,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(order_tracking: OrderTracking) -> Order {
+        Order {
+            order_id: [0; 16],
+            buyer: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            courier: Some(Pubkey::new_unique()),
+            order_index: 0,
+            payment: Pubkey::default(),
+            escrow: Pubkey::default(),
+            items: Vec::new(),
+            tracking_id: [0; 16],
+            order_status: OrderStatus::Placed,
+            order_tracking,
+            created_at: 0,
+            updated_at: 0,
+            order_bump: 0,
+        }
+    }
+
+    #[test]
+    fn buyer_can_confirm_once_shipped() {
+        let shipped = order(OrderTracking::Shipped);
+        assert!(shipped.can_confirm_delivery(&shipped.buyer));
+        assert!(!shipped.can_confirm_delivery(&shipped.courier.unwrap()));
+
+        let out = order(OrderTracking::OutForDelivery);
+        assert!(out.can_confirm_delivery(&out.buyer));
+        assert!(out.can_confirm_delivery(&out.courier.unwrap()));
+
+        let in_transit = order(OrderTracking::InTransit);
+        assert!(!in_transit.can_confirm_delivery(&in_transit.buyer));
+
+        let delivered = order(OrderTracking::Delivered);
+        assert!(!delivered.can_confirm_delivery(&delivered.buyer));
+    }
+}
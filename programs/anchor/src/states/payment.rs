@@ -1,12 +1,19 @@
 use anchor_lang::prelude::*;
+use crate::error::EcomError;
 
 #[account]
 #[derive(InitSpace)]
 pub struct Payment{
     pub payment_id: [u8;16],
+    pub owner: Pubkey,
+    pub payment_nonce: u64,
     pub payment_amount: u64,
-    pub product_pubkey:Pubkey,
+    // None for cart checkouts; the order's items hold the lines
+    pub product_pubkey:Option<Pubkey>,
+    pub quantity:u32,
+    pub escrow:Option<Pubkey>,
     pub payment_method:PaymentMethod,
+    pub mint:Option<Pubkey>,
     pub payment_status:PaymentStatus,
     pub time_stamp:i64,
     #[max_len(3)]
@@ -14,6 +21,26 @@ pub struct Payment{
     pub payment_bump:u8,
 }
 
+/// Highest payment nonce a buyer has used. Nonces must increase, so a closed
+/// payment's address, and the escrow and dispute derived from it, is never
+/// handed to a new purchase.
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentCounter{
+    pub buyer:Pubkey,
+    pub last_nonce:u64,
+    pub counter_bump:u8,
+}
+
+impl PaymentCounter {
+    pub fn use_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(nonce > self.last_nonce, EcomError::PaymentNonceUsed);
+        self.last_nonce = nonce;
+        Ok(())
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq, Eq)]
 pub enum PaymentMethod {
     SOL,
@@ -28,4 +55,23 @@ pub enum PaymentStatus {
     Success,
     Pending,
     Failed,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nonces_cannot_be_reused() {
+        let mut counter = PaymentCounter {
+            buyer: Pubkey::default(),
+            last_nonce: 0,
+            counter_bump: 0,
+        };
+        counter.use_nonce(5).unwrap();
+        assert!(counter.use_nonce(5).is_err());
+        assert!(counter.use_nonce(3).is_err());
+        counter.use_nonce(6).unwrap();
+        assert_eq!(counter.last_nonce, 6);
+    }
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::error::EcomError;
 
 #[account]
 #[derive(InitSpace)]
@@ -10,35 +11,213 @@ pub struct Product {
     pub division: Division,
     pub quantity: u32,
     pub seller_pubkey: Pubkey,
-    #[max_len(50)]
-    pub seller_name: String,
     #[max_len(300)]
     pub product_short_description: String,
     #[max_len(150)]
     pub product_imgurl: String,
     pub price: u32,
-    pub rating: f32,
+    // Currency of `price`: None for lamports, or an accepted SPL mint
+    pub mint: Option<Pubkey>,
+    // Sum and count of 1-5 review scores; average = rating_sum / rating_count
+    pub rating_sum: u64,
+    pub rating_count: u32,
     pub stock_status: Stock,
     pub creation_bump:u8,
 }
 
+impl Product {
+    /// Takes `quantity` units out of stock for a purchase.
+    pub fn reserve_stock(&mut self, quantity: u32) -> Result<()> {
+        require!(
+            self.stock_status != Stock::OutOfStock && quantity <= self.quantity,
+            EcomError::InsufficientStock
+        );
+        self.quantity -= quantity;
+        if self.quantity == 0 {
+            self.stock_status = Stock::OutOfStock;
+        } else if self.stock_status == Stock::Restoring {
+            self.stock_status = Stock::InStock;
+        }
+        Ok(())
+    }
+
+    /// Folds a verified buyer's review score into the running totals.
+    pub fn add_rating(&mut self, score: u8) -> Result<()> {
+        self.rating_sum = self.rating_sum
+            .checked_add(u64::from(score))
+            .ok_or(EcomError::UnexpectedError)?;
+        self.rating_count = self.rating_count
+            .checked_add(1)
+            .ok_or(EcomError::UnexpectedError)?;
+        Ok(())
+    }
+
+    /// Adds `quantity` units; a sold-out product moves to `Restoring`.
+    pub fn restock(&mut self, quantity: u32) -> Result<()> {
+        require!(quantity > 0, EcomError::InvalidQuantity);
+        self.quantity = self.quantity
+            .checked_add(quantity)
+            .ok_or(EcomError::InvalidQuantity)?;
+        if self.stock_status == Stock::OutOfStock {
+            self.stock_status = Stock::Restoring;
+        }
+        Ok(())
+    }
+
+    /// Puts back `quantity` units reserved by a purchase that was refunded
+    /// or lost in a dispute.
+    pub fn release_stock(&mut self, quantity: u32) -> Result<()> {
+        let quantity = self.quantity
+            .checked_add(quantity)
+            .ok_or(EcomError::InvalidQuantity)?;
+        self.set_quantity(quantity);
+        Ok(())
+    }
+
+    /// Sets the units on hand; `stock_status` always follows the quantity,
+    /// so an empty product is `OutOfStock` and one refilled from empty is
+    /// `Restoring` until its next sale.
+    pub fn set_quantity(&mut self, quantity: u32) {
+        self.quantity = quantity;
+        if quantity == 0 {
+            self.stock_status = Stock::OutOfStock;
+        } else if self.stock_status == Stock::OutOfStock {
+            self.stock_status = Stock::Restoring;
+        }
+    }
+
+    /// Lets the seller mark a product out of stock or back in stock by
+    /// hand; an empty product can only be `OutOfStock`.
+    pub fn set_stock_status(&mut self, stock_status: Stock) -> Result<()> {
+        require!(
+            self.quantity > 0 || stock_status == Stock::OutOfStock,
+            EcomError::InvalidStockStatus
+        );
+        self.stock_status = stock_status;
+        Ok(())
+    }
+}
+
+/// Root of a seller's catalogue; the listings themselves live in
+/// `ProductPage`s indexed `0..page_count`.
 #[account]
 #[derive(InitSpace)]
 pub struct ProductsList{
-    #[max_len(40)]
-    pub products:Vec<Pubkey>,
+    pub seller:Pubkey,
+    pub page_count:u32,
+    pub total_products:u32,
     pub product_list_bump:u8,
 }
+
+impl ProductsList {
+    /// Page the next listing goes on. Removals keep every page but the
+    /// last one full, so this is always the last page or a new one.
+    pub fn tail_page_index(&self) -> u32 {
+        self.total_products / ProductPage::CAPACITY as u32
+    }
+
+    pub fn last_page_index(&self) -> u32 {
+        self.page_count.saturating_sub(1)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProductPage{
+    pub seller:Pubkey,
+    pub page_index:u32,
+    #[max_len(32)]
+    pub products:Vec<Pubkey>,
+    pub page_bump:u8,
+}
+
+impl ProductPage {
+    pub const CAPACITY: usize = 32;
+}
+
+/// Takes `key` off `page` while keeping a paged list compact: the final
+/// entry of `last_page` fills the gap, or, when `page` is itself the last
+/// page (`last_page` is None), the entry is swap-removed in place. Returns
+/// whether the last page is now empty and should be closed.
+pub fn remove_from_pages(
+    page: &mut Vec<Pubkey>,
+    last_page: Option<&mut Vec<Pubkey>>,
+    key: &Pubkey,
+) -> Result<bool> {
+    let position = page
+        .iter()
+        .position(|entry| entry == key)
+        .ok_or(EcomError::ProductPageMismatch)?;
+    match last_page {
+        None => {
+            page.swap_remove(position);
+            Ok(page.is_empty())
+        }
+        Some(last_page) => {
+            let moved = last_page.pop().ok_or(EcomError::MissingProductPage)?;
+            page[position] = moved;
+            Ok(last_page.is_empty())
+        }
+    }
+}
+
+/// Root of a marketplace-wide index of every listing in one `Category` or
+/// `Division`; its entries live in `ProductIndexPage`s indexed `0..page_count`.
+#[account]
+#[derive(InitSpace)]
+pub struct ProductIndex{
+    pub page_count:u32,
+    pub total_products:u32,
+    pub index_bump:u8,
+}
+
+impl ProductIndex {
+    /// Page the next listing goes on; pages are compacted like `ProductsList`.
+    pub fn tail_page_index(&self) -> u32 {
+        self.total_products / ProductIndexPage::CAPACITY as u32
+    }
+
+    pub fn last_page_index(&self) -> u32 {
+        self.page_count.saturating_sub(1)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProductIndexPage{
+    pub page_index:u32,
+    // Seller who paid the page's rent; it is refunded to them when the page closes
+    pub payer:Pubkey,
+    #[max_len(32)]
+    pub products:Vec<Pubkey>,
+    pub page_bump:u8,
+}
+
+impl ProductIndexPage {
+    pub const CAPACITY: usize = 32;
+}
 #[event]
 pub struct ProductCreated {
     pub product_pubkey: Pubkey,
     pub seller: Pubkey,
     pub product_name: String,
     pub price: u32,
+    pub mint: Option<Pubkey>,
     pub category: Category,
     pub division: Division,
 }
 
+#[event]
+pub struct ProductUpdated {
+    pub product_pubkey: Pubkey,
+    pub seller: Pubkey,
+    pub price: u32,
+    pub quantity: u32,
+    pub category: Category,
+    pub division: Division,
+    pub stock_status: Stock,
+}
+
 #[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace)]
 pub enum Category {
     Electronics,
@@ -57,10 +236,143 @@ pub enum Division {
     ComputerPeripherals,
 }
 
-#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace)]
+impl Category {
+    /// Seed byte used by the `category_index` PDAs.
+    pub fn index_seed(&self) -> [u8; 1] {
+        [self.clone() as u8]
+    }
+}
+
+impl Division {
+    /// Seed byte used by the `division_index` PDAs.
+    pub fn index_seed(&self) -> [u8; 1] {
+        [self.clone() as u8]
+    }
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq, Eq)]
 pub enum Stock {
     OutOfStock,
     InStock,
     Restoring
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(page_count: u32, total_products: u32) -> ProductsList {
+        ProductsList {
+            seller: Pubkey::default(),
+            page_count,
+            total_products,
+            product_list_bump: 0,
+        }
+    }
+
+    #[test]
+    fn new_page_starts_once_tail_is_full() {
+        let capacity = ProductPage::CAPACITY as u32;
+        assert_eq!(list(0, 0).tail_page_index(), 0);
+        assert_eq!(list(1, capacity - 1).tail_page_index(), 0);
+        assert_eq!(list(1, capacity).tail_page_index(), 1);
+        assert_eq!(list(2, capacity + 1).last_page_index(), 1);
+        assert_eq!(list(0, 0).last_page_index(), 0);
+    }
+
+    fn keys(count: usize) -> Vec<Pubkey> {
+        (0..count).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn removing_from_a_middle_page_pulls_in_the_last_entry() {
+        let mut page = keys(ProductPage::CAPACITY);
+        let mut last_page = keys(3);
+        let removed = page[5];
+        let moved = last_page[2];
+
+        let emptied = remove_from_pages(&mut page, Some(&mut last_page), &removed).unwrap();
+        assert!(!emptied);
+        assert_eq!(page.len(), ProductPage::CAPACITY);
+        assert_eq!(page[5], moved);
+        assert!(!page.contains(&removed));
+        assert_eq!(last_page.len(), 2);
+    }
+
+    #[test]
+    fn removing_from_the_last_page_swaps_in_place() {
+        let mut page = keys(3);
+        let (removed, middle, tail) = (page[0], page[1], page[2]);
+
+        let emptied = remove_from_pages(&mut page, None, &removed).unwrap();
+        assert!(!emptied);
+        assert_eq!(page, vec![tail, middle]);
+        assert!(remove_from_pages(&mut page, None, &removed).is_err());
+    }
+
+    #[test]
+    fn emptying_the_tail_page_reports_it_for_closing() {
+        let mut page = keys(ProductPage::CAPACITY);
+        let mut last_page = keys(1);
+        let removed = page[0];
+        let moved = last_page[0];
+
+        let emptied = remove_from_pages(&mut page, Some(&mut last_page), &removed).unwrap();
+        assert!(emptied);
+        assert_eq!(page[0], moved);
+        assert!(last_page.is_empty());
+
+        let mut only_page = keys(1);
+        let last = only_page[0];
+        assert!(remove_from_pages(&mut only_page, None, &last).unwrap());
+
+        // An already emptied last page has nothing left to fill a gap with
+        let next = page[1];
+        assert!(remove_from_pages(&mut page, Some(&mut last_page), &next).is_err());
+    }
+
+    #[test]
+    fn index_seeds_follow_variant_order() {
+        assert_eq!(Category::Electronics.index_seed(), [0]);
+        assert_eq!(Category::GroceryAndKitchen.index_seed(), [4]);
+        assert_eq!(Division::Laptop.index_seed(), [1]);
+    }
+
+    #[test]
+    fn stock_status_follows_quantity() {
+        let mut product = Product {
+            product_id: [0; 16],
+            product_name: String::new(),
+            category: Category::Electronics,
+            division: Division::Laptop,
+            quantity: 2,
+            seller_pubkey: Pubkey::default(),
+            product_short_description: String::new(),
+            product_imgurl: String::new(),
+            price: 10,
+            mint: None,
+            rating_sum: 0,
+            rating_count: 0,
+            stock_status: Stock::InStock,
+            creation_bump: 0,
+        };
+
+        product.reserve_stock(2).unwrap();
+        assert!(product.stock_status == Stock::OutOfStock);
+
+        product.release_stock(2).unwrap();
+        assert_eq!(product.quantity, 2);
+        assert!(product.stock_status == Stock::Restoring);
+
+        product.set_quantity(0);
+        assert!(product.stock_status == Stock::OutOfStock);
+        assert!(product.reserve_stock(1).is_err());
+        assert!(product.set_stock_status(Stock::InStock).is_err());
+
+        product.set_quantity(3);
+        product.set_stock_status(Stock::OutOfStock).unwrap();
+        assert!(product.reserve_stock(1).is_err());
+    }
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Review{
+    pub reviewer:Pubkey,
+    pub order:Pubkey,
+    pub product:Pubkey,
+    pub score:u8,
+    pub content_hash:[u8;32],
+    #[max_len(200)]
+    pub uri:String,
+    pub created_at:i64,
+    pub review_bump:u8,
+}
+
+impl Review {
+    pub const MIN_SCORE: u8 = 1;
+    pub const MAX_SCORE: u8 = 5;
+    pub const MAX_URI_LEN: usize = 200;
+}
+
+#[event]
+pub struct ReviewSubmitted {
+    pub review: Pubkey,
+    pub order: Pubkey,
+    pub product: Pubkey,
+    pub reviewer: Pubkey,
+    pub score: u8,
+    pub rating_sum: u64,
+    pub rating_count: u32,
+}
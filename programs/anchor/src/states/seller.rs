@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct SellerProfile{
+    pub seller:Pubkey,
+    #[max_len(50)]
+    pub display_name:String,
+    pub contact_hash:[u8;32],
+    #[max_len(150)]
+    pub logo_uri:String,
+    pub joined_at:i64,
+    // Set by the marketplace authority
+    pub verified:bool,
+    pub suspended:bool,
+    pub total_sales:u64,
+    pub active_listings:u32,
+    // Reputation, updated as escrows settle
+    pub orders_completed:u64,
+    pub refunds:u64,
+    pub disputes_lost:u64,
+    pub delivered_orders:u64,
+    pub total_delivery_time:i64,
+    pub profile_bump:u8,
+}
+
+impl SellerProfile {
+    pub const MAX_DISPLAY_NAME_LEN: usize = 50;
+    pub const MAX_LOGO_URI_LEN: usize = 150;
+
+    /// Counts a completed sale; `delivery_time` is the seconds from order
+    /// creation to confirmed delivery, when the order went through delivery.
+    pub fn record_completed_order(&mut self, delivery_time: Option<i64>) {
+        self.orders_completed = self.orders_completed.saturating_add(1);
+        if let Some(delivery_time) = delivery_time {
+            self.delivered_orders = self.delivered_orders.saturating_add(1);
+            self.total_delivery_time = self.total_delivery_time
+                .saturating_add(delivery_time.max(0));
+        }
+    }
+
+    pub fn record_refund(&mut self) {
+        self.refunds = self.refunds.saturating_add(1);
+    }
+
+    pub fn record_dispute_lost(&mut self) {
+        self.disputes_lost = self.disputes_lost.saturating_add(1);
+    }
+
+    /// Average seconds from order creation to delivery, if any were delivered.
+    pub fn average_delivery_time(&self) -> Option<i64> {
+        i64::try_from(self.delivered_orders)
+            .ok()
+            .filter(|count| *count > 0)
+            .map(|count| self.total_delivery_time / count)
+    }
+
+    pub fn reputation(&self) -> SellerReputationUpdated {
+        SellerReputationUpdated {
+            seller: self.seller,
+            orders_completed: self.orders_completed,
+            refunds: self.refunds,
+            disputes_lost: self.disputes_lost,
+            delivered_orders: self.delivered_orders,
+            average_delivery_time: self.average_delivery_time().unwrap_or_default(),
+        }
+    }
+}
+
+#[event]
+pub struct SellerRegistered {
+    pub seller: Pubkey,
+    pub profile: Pubkey,
+    pub display_name: String,
+    pub joined_at: i64,
+}
+
+#[event]
+pub struct SellerStatusChanged {
+    pub seller: Pubkey,
+    pub verified: bool,
+    pub suspended: bool,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct SellerReputationUpdated {
+    pub seller: Pubkey,
+    pub orders_completed: u64,
+    pub refunds: u64,
+    pub disputes_lost: u64,
+    pub delivered_orders: u64,
+    pub average_delivery_time: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> SellerProfile {
+        SellerProfile {
+            seller: Pubkey::default(),
+            display_name: String::new(),
+            contact_hash: [0; 32],
+            logo_uri: String::new(),
+            joined_at: 0,
+            verified: false,
+            suspended: false,
+            total_sales: 0,
+            active_listings: 0,
+            orders_completed: 0,
+            refunds: 0,
+            disputes_lost: 0,
+            delivered_orders: 0,
+            total_delivery_time: 0,
+            profile_bump: 0,
+        }
+    }
+
+    #[test]
+    fn average_delivery_time_only_counts_delivered_orders() {
+        let mut profile = profile();
+        assert_eq!(profile.average_delivery_time(), None);
+
+        profile.record_completed_order(Some(100));
+        profile.record_completed_order(None);
+        profile.record_completed_order(Some(300));
+
+        assert_eq!(profile.orders_completed, 3);
+        assert_eq!(profile.delivered_orders, 2);
+        assert_eq!(profile.average_delivery_time(), Some(200));
+    }
+}
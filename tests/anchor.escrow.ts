@@ -1,7 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { EcomDapp } from "../target/types/ecom_dapp";
-import { LAMPORTS_PER_SOL, PublicKey, SystemProgram, Transaction } from "@solana/web3.js";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram, Transaction } from "@solana/web3.js";
 import { SYSTEM_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/native/system";
 import { expect } from "chai";
 import { BN } from "@coral-xyz/anchor";
@@ -45,6 +45,21 @@ describe("anchor", () => {
   let payment_tx:string;
   let escrowPda: PublicKey;
   let paymentPda: PublicKey;
+  let orderPda: PublicKey;
+  let productPda: PublicKey;
+  // Each purchase gets its own payment/escrow/vault PDAs keyed by this nonce.
+  const PAYMENT_NONCE = new BN(Date.now());
+  let orderCounterPda: PublicKey;
+
+  const orderPdaFor = (orderIndex: BN): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("order"),
+        signer.publicKey.toBuffer(),
+        orderIndex.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
 
   const fundIfNeeded = async (pubkey: PublicKey) => {
     const bal = await provider.connection.getBalance(pubkey);
@@ -64,6 +79,58 @@ describe("anchor", () => {
   ]);
   });
 
+  const PLATFORM_FEE_BPS = 250;
+  const PRODUCT_PAGE_CAPACITY = 32;
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("marketplace_config")],
+    program.programId
+  );
+  const [treasuryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+  const [sellerProfilePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("seller"), signer.publicKey.toBuffer()],
+    program.programId
+  );
+  // initializeMarketplace is restricted to the program's upgrade authority
+  const [programDataPda] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  before(async () => {
+    try {
+      await program.account.marketplaceConfig.fetch(configPda);
+    } catch (error) {
+      await program.methods
+        .initializeMarketplace(signer.publicKey, PLATFORM_FEE_BPS)
+        .accounts({
+          authority: signer.publicKey,
+          config: configPda,
+          treasury: treasuryPda,
+          program: program.programId,
+          programData: programDataPda,
+          systemProgram: SystemProgram.programId,
+        } as any)
+        .rpc();
+      console.log("Marketplace config initialized", configPda.toBase58());
+    }
+    try {
+      await program.account.sellerProfile.fetch(sellerProfilePda);
+    } catch (error) {
+      await program.methods
+        .registerSeller("Test Seller", Array(32).fill(0), "https://example.com/logo.png")
+        .accounts({
+          seller: signer.publicKey,
+          sellerProfile: sellerProfilePda,
+          config: configPda,
+          systemProgram: SystemProgram.programId,
+        } as any)
+        .rpc();
+    }
+  });
+
   function bytesToUuid(bytes: number[]): string {
     if (bytes.length !== 16) throw new Error("Invalid UUID length");
 
@@ -77,19 +144,110 @@ describe("anchor", () => {
     ].join("-");
   }
 
+  async function indexPdas(prefix: string, variant: number): Promise<[PublicKey, PublicKey]> {
+    const [indexPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), Buffer.from([variant])],
+      program.programId
+    );
+    const totalProducts = await program.account.productIndex
+      .fetch(indexPda)
+      .then((index) => index.totalProducts)
+      .catch(() => 0);
+    const page = Buffer.alloc(4);
+    page.writeUInt32LE(Math.floor(totalProducts / PRODUCT_PAGE_CAPACITY));
+    const [pagePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from(prefix), Buffer.from([variant]), page],
+      program.programId
+    );
+    return [indexPda, pagePda];
+  }
+
+  // Accounts for listing `productName` as an Electronics / Laptop product;
+  // Electronics and Laptop are variants 0 and 1 of Category and Division
+  const listingAccounts = async (productName: string) => {
+    const [product] = PublicKey.findProgramAddressSync(
+      [Buffer.from("product"), signer.publicKey.toBuffer(), Buffer.from(productName)],
+      program.programId
+    );
+    const [productList] = PublicKey.findProgramAddressSync(
+      [Buffer.from("product_list"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const totalProducts = await program.account.productsList
+      .fetch(productList)
+      .then((list) => list.totalProducts)
+      .catch(() => 0);
+    const pageIndex = Buffer.alloc(4);
+    pageIndex.writeUInt32LE(Math.floor(totalProducts / PRODUCT_PAGE_CAPACITY));
+    const [productPage] = PublicKey.findProgramAddressSync(
+      [Buffer.from("product_page"), signer.publicKey.toBuffer(), pageIndex],
+      program.programId
+    );
+    const [categoryIndex, categoryPage] = await indexPdas("category_index", 0);
+    const [divisionIndex, divisionPage] = await indexPdas("division_index", 1);
+    return {
+      seller: signer.publicKey,
+      product,
+      productList,
+      productPage,
+      categoryIndex,
+      categoryPage,
+      divisionIndex,
+      divisionPage,
+      config: configPda,
+      sellerProfile: sellerProfilePda,
+      systemProgram: SystemProgram.programId,
+    };
+  };
+
+  it("creates product PDA", async () => {
+    const productName = `Escrow Test ${PAYMENT_NONCE.toString()}`;
+    const listing = await listingAccounts(productName);
+    productPda = listing.product;
+    await program.methods
+      .createProduct(
+        productName,
+        "Product used by the escrow flow tests",
+        Number(PAYMENT_AMOUNT),
+        { electronics: {} },
+        { laptop: {} },
+        "https://example.com/product.png",
+        10,
+        // priced in lamports
+        null
+      )
+      .accounts(listing as any)
+      .rpc();
+    const product = await program.account.product.fetch(productPda);
+    expect(product.price).to.equal(Number(PAYMENT_AMOUNT));
+    expect(product.quantity).to.equal(10);
+  });
+
   it("creates payment PDA", async () => {
     const amount = new BN(Number(PAYMENT_AMOUNT));
     console.log("Amount: ",amount.toNumber());
     const [newPaymentPda] = PublicKey.findProgramAddressSync(
-    [Buffer.from("payment"), owner.toBuffer()],
+    [
+      Buffer.from("payment"),
+      owner.toBuffer(),
+      PAYMENT_NONCE.toArrayLike(Buffer, "le", 8),
+    ],
     program.programId
     );
     console.log("Payment PDA created", newPaymentPda);
     await program.methods
-      .createPayment(amount, newPaymentPda, null)
+      .createPayment(PAYMENT_NONCE, amount, 1, null)
       .accounts({
         signer: owner,
         payments: newPaymentPda,
+        paymentCounter: PublicKey.findProgramAddressSync(
+          [Buffer.from("payment_counter"), owner.toBuffer()],
+          program.programId
+        )[0],
+        product: productPda,
+        config: configPda,
+        // SOL payment: no SPL mint
+        mint: null,
         systemProgram: SystemProgram.programId,
       }as any)
       .rpc();
@@ -98,15 +256,18 @@ describe("anchor", () => {
       console.log("Payment Status: ",paymentDetails.paymentStatus);
       console.log("Payment PDA created", paymentPda);
       expect(paymentDetails.paymentStatus).to.deep.equal({pending : {}});
+      // Nothing is reserved until the escrow is funded
+      const product = await program.account.product.fetch(productPda);
+      expect(product.quantity).to.equal(10);
   });
 
   it("creates escrow", async () => {
     [escrowPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("escrow"), owner.toBuffer()],
+      [Buffer.from("escrow"), paymentPda.toBuffer()],
       program.programId
     );
     const vaultState = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("state"), provider.publicKey.toBuffer()],
+      [Buffer.from("state"), paymentPda.toBuffer()],
       program.programId
     )[0];
 
@@ -133,11 +294,14 @@ describe("anchor", () => {
     } catch (error) {
       const amount = new BN(Number(PAYMENT_AMOUNT));
       await program.methods
-        .createEscrow(buyer, seller, amount)
+        .createEscrow(amount)
         .accounts({
           owner: owner,
           escrow: escrowPda,
           payment: paymentPda,
+          product: productPda,
+          sellerProfile: sellerProfilePda,
+          config: configPda,
           systemProgram: SystemProgram.programId,
         }as any)
         .rpc();
@@ -162,7 +326,7 @@ describe("anchor", () => {
     console.log("paymentPda: ",paymentPda);
 
     const vaultState = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("state"), provider.publicKey.toBuffer()],
+      [Buffer.from("state"), paymentPda.toBuffer()],
       program.programId
     )[0];
 
@@ -172,15 +336,17 @@ describe("anchor", () => {
     )[0];
 
     const tx = await program.methods
-      .depositEscrow(1)
+      .depositEscrow()
       .accounts({
         owner: owner,
         escrow: escrowPda,
         payment: paymentPda,
+        product: productPda,
         vaultState: vaultState,
         vault:vault,
         escrowAccount:escrowPda,
         user:owner,
+        config: configPda,
         systemProgram: SystemProgram.programId,
       }as any)
       .rpc();
@@ -190,6 +356,8 @@ describe("anchor", () => {
     const escrow = await program.account.escrow.fetch(escrowPda);
     expect(escrow.escrowStatus).to.deep.equal({ fundsReceived: {} });
     expect(escrow.releaseFund).to.be.true;
+    const product = await program.account.product.fetch(productPda);
+    expect(product.quantity).to.equal(9);
 
     const userBal = await provider.connection.getBalance(owner);
     const vaultBal = await provider.connection.getBalance(vault);
@@ -204,63 +372,20 @@ describe("anchor", () => {
     expect(payment.paymentMethod).to.deep.equal({sol : {}})
   });
 
-it("withdraws from escrow", async () => {
-    const vaultState = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("state"), provider.publicKey.toBuffer()],
-      program.programId
-    )[0];
-
-    const vault = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("vault"), vaultState.toBytes()],
-      program.programId
-    )[0];
-    console.log("Before:", await provider.connection.getAccountInfo(vault));
-    const tx = await program.methods
-      .withdrawEscrow(1)
-      .accounts({
-        owner: owner,
-        escrow: escrowPda,
-        payment: paymentPda,
-        vaultState:vaultState,
-        vault:vault,
-        sellerAccount:seller,
-        systemProgram: SystemProgram.programId,
-      }as any)
-      .rpc();
-    console.log("Withdraw tx:", tx);
-
-    console.log("Before:", await provider.connection.getAccountInfo(vault));
-    
-    const sellerBal = await provider.connection.getBalance(seller);
-    const vaultBal = await provider.connection.getBalance(vault);
-      
-    console.log("Account Balances:");
-    console.log(`Vault (${escrowPda.toString()}): ${vaultBal} SOL`);
-    console.log(`Seller (${owner.toString()}): ${sellerBal} SOL`);
-
-    const escrow = await program.account.escrow.fetch(escrowPda);
-
-    expect(escrow.escrowStatus).to.deep.equal({ swapSuccess: {} });
-    expect(escrow.releaseFund).to.be.false;
-
-    const payment = await program.account.payment.fetch(paymentPda);
-    expect(payment.paymentStatus).to.deep.equal({ success: {} });
-
-    // expect(Number(sellerBal.value.amount)).to.equal(PAYMENT_AMOUNT);
-    if (expect(payment.paymentStatus).to.have.property("success")) {
-      payment.txSignature = payment_tx;
-    }else{
-      console.log("Payment Not Intialized!");
-    }
-    console.log("Swap Payment Completed successfully..");
-    console.log("payment invoice: ",payment);
-  });
-
   it("should place order and show details",async()=>{
-    const [orderPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("order"), signer.publicKey.toBuffer()],
+    [orderCounterPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_counter"), signer.publicKey.toBuffer()],
       program.programId
-    );console.log("Order PDA:", orderPda.toBase58());
+    );
+    let orderIndex = new BN(0);
+    try {
+      const counter = await program.account.orderCounter.fetch(orderCounterPda);
+      orderIndex = new BN(counter.orderCount);
+    } catch (error) {
+      console.log("Order counter not initialized yet, using index 0");
+    }
+    orderPda = orderPdaFor(orderIndex);
+    console.log("Order PDA:", orderPda.toBase58());
 
     try {
       const existingPayment = await program.account.payment.fetch(paymentPda);
@@ -277,13 +402,15 @@ it("withdraws from escrow", async () => {
   
       }
     } catch (error) {
-      const payment_id = (await program.account.payment.fetch(paymentPda)).paymentId;
-      const order_tx = await program.methods.createOrder(
-        String(bytesToUuid(payment_id)),
-      ).accounts({
+      // The order links to the funded payment/escrow; the escrow seller is the
+      // test wallet, so it also drives tracking updates.
+      const order_tx = await program.methods.createOrder().accounts({
         signer: signer.publicKey,
+        orderCounter: orderCounterPda,
         order: orderPda,
         payment:paymentPda,
+        escrow:escrowPda,
+        config: configPda,
         systemProgram: SYSTEM_PROGRAM_ID,
       } as any).rpc();
 
@@ -298,31 +425,27 @@ it("withdraws from escrow", async () => {
       console.log("Order Tracking: ",order.orderTracking);
 
       expect(order.orderStatus).to.have.property("placed");
+      expect(order.payment.toBase58()).to.equal(paymentPda.toBase58());
+      expect(order.escrow.toBase58()).to.equal(escrowPda.toBase58());
     }
   });
 
   it("should update order status to in-transit and show details",async()=>{
-    const [orderPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("order"), signer.publicKey.toBuffer()],
-      program.programId
-    );console.log("Order PDA:", orderPda.toBase58());
-
-    const [paymentPda] = PublicKey.findProgramAddressSync(
-    [Buffer.from("payment"), owner.toBuffer()],
-    program.programId
-    );console.log("Payment PDA: ",paymentPda);
+    console.log("Order PDA:", orderPda.toBase58());
+    console.log("Payment PDA: ",paymentPda);
 
     const existingPayment = await program.account.payment.fetch(paymentPda);
     const existingOrder = await program.account.order.fetch(orderPda);
     
     if (existingPayment && existingOrder) {
-      const orderStatusUpdate = "intransit";
+      const orderStatusUpdate = { inTransit: {} };
       try {
         await program.methods.updateOrder(
           orderStatusUpdate
         ).accounts({
           signer:signer.publicKey,
-          order:orderPda
+          order:orderPda,
+          config: configPda,
         }as any).rpc()
         console.log("Order status updated Successfully...");
         console.log("Order Tracking: ",existingOrder.orderTracking);
@@ -333,27 +456,21 @@ it("withdraws from escrow", async () => {
   });
 
   it("should update order status to shipped and show details",async()=>{
-    const [orderPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("order"), signer.publicKey.toBuffer()],
-      program.programId
-    );console.log("Order PDA:", orderPda.toBase58());
-
-    const [paymentPda] = PublicKey.findProgramAddressSync(
-    [Buffer.from("payment"), owner.toBuffer()],
-    program.programId
-    );console.log("Payment PDA: ",paymentPda);
+    console.log("Order PDA:", orderPda.toBase58());
+    console.log("Payment PDA: ",paymentPda);
 
     const existingPayment = await program.account.payment.fetch(paymentPda);
     const existingOrder = await program.account.order.fetch(orderPda);
     
     if (existingPayment && existingOrder) {
-      const orderStatusUpdate = "shipped";
+      const orderStatusUpdate = { shipped: {} };
       try {
         await program.methods.updateOrder(
           orderStatusUpdate
         ).accounts({
           signer:signer.publicKey,
-          order:orderPda
+          order:orderPda,
+          config: configPda,
         }as any).rpc()
         console.log("Order status updated Successfully...");
         console.log("Order Tracking: ",existingOrder.orderTracking);
@@ -364,15 +481,8 @@ it("withdraws from escrow", async () => {
   });
   
   it("should place order and show details",async()=>{
-    const [orderPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("order"), signer.publicKey.toBuffer()],
-      program.programId
-    );console.log("Order PDA:", orderPda.toBase58());
-
-    const [paymentPda] = PublicKey.findProgramAddressSync(
-    [Buffer.from("payment"), owner.toBuffer()],
-    program.programId
-    );console.log("Payment PDA: ",paymentPda);
+    console.log("Order PDA:", orderPda.toBase58());
+    console.log("Payment PDA: ",paymentPda);
 
     const existingPayment = await program.account.payment.fetch(paymentPda);
     const existingOrder = await program.account.order.fetch(orderPda);
@@ -388,10 +498,70 @@ it("withdraws from escrow", async () => {
     }
   });
 
+  it("withdraws from escrow", async () => {
+    const vaultState = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("state"), paymentPda.toBuffer()],
+      program.programId
+    )[0];
+
+    const vault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), vaultState.toBytes()],
+      program.programId
+    )[0];
+    console.log("Before:", await provider.connection.getAccountInfo(vault));
+    const tx = await program.methods
+      .withdrawEscrow()
+      .accounts({
+        owner: owner,
+        escrow: escrowPda,
+        payment: paymentPda,
+        vaultState:vaultState,
+        vault:vault,
+        sellerAccount:signer.publicKey,
+        sellerProfile: sellerProfilePda,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+      }as any)
+      .rpc();
+    console.log("Withdraw tx:", tx);
+
+    const released = await program.account.escrow.fetch(escrowPda);
+    const expectedFee = Math.floor((Number(PAYMENT_AMOUNT) * PLATFORM_FEE_BPS) / 10_000);
+    expect(released.platformFee.toNumber()).to.equal(expectedFee);
+    expect(released.sellerPayout.toNumber()).to.equal(Number(PAYMENT_AMOUNT) - expectedFee);
+
+    console.log("Before:", await provider.connection.getAccountInfo(vault));
+    
+    const sellerBal = await provider.connection.getBalance(signer.publicKey);
+    const vaultBal = await provider.connection.getBalance(vault);
+      
+    console.log("Account Balances:");
+    console.log(`Vault (${escrowPda.toString()}): ${vaultBal} SOL`);
+    console.log(`Seller (${owner.toString()}): ${sellerBal} SOL`);
+
+    const escrow = await program.account.escrow.fetch(escrowPda);
+
+    expect(escrow.escrowStatus).to.deep.equal({ swapSuccess: {} });
+    expect(escrow.releaseFund).to.be.false;
+
+    const payment = await program.account.payment.fetch(paymentPda);
+    expect(payment.paymentStatus).to.deep.equal({ success: {} });
+
+    // expect(Number(sellerBal.value.amount)).to.equal(PAYMENT_AMOUNT);
+    if (expect(payment.paymentStatus).to.have.property("success")) {
+      payment.txSignature = payment_tx;
+    }else{
+      console.log("Payment Not Intialized!");
+    }
+    console.log("Swap Payment Completed successfully..");
+    console.log("payment invoice: ",payment);
+  });
+
   it("Close All PDA's..",async()=>{
     try {
       const vaultState = anchor.web3.PublicKey.findProgramAddressSync(
-        [Buffer.from("state"), provider.publicKey.toBuffer()],
+        [Buffer.from("state"), paymentPda.toBuffer()],
         program.programId
       )[0];
 
@@ -416,4 +586,550 @@ it("withdraws from escrow", async () => {
       console.log("Failed: Not able to close payment",error.message);
     }
   });
+
+  const checkoutPdas = async (nonce: BN) => {
+    const [cartListPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("cart_list"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [cartPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("cart"), signer.publicKey.toBuffer(), productPda.toBuffer()],
+      program.programId
+    );
+    const [payment] = PublicKey.findProgramAddressSync(
+      [Buffer.from("payment"), signer.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [escrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), payment.toBuffer()],
+      program.programId
+    );
+    const [vaultState] = PublicKey.findProgramAddressSync(
+      [Buffer.from("state"), payment.toBuffer()],
+      program.programId
+    );
+    const [vault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), vaultState.toBuffer()],
+      program.programId
+    );
+    const [counter] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_counter"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const orderIndex = await program.account.orderCounter
+      .fetch(counter)
+      .then((c) => new BN(c.orderCount))
+      .catch(() => new BN(0));
+    return {
+      cartListPda,
+      cartPda,
+      accounts: {
+        buyer: signer.publicKey,
+        cartList: cartListPda,
+        config: configPda,
+        sellerProfile: sellerProfilePda,
+        payment,
+        escrow,
+        vaultState,
+        vault,
+        paymentCounter: PublicKey.findProgramAddressSync(
+          [Buffer.from("payment_counter"), signer.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        orderCounter: counter,
+        order: orderPdaFor(orderIndex),
+        mint: null,
+        vaultTokenAccount: null,
+        buyerTokenAccount: null,
+        tokenProgram: null,
+        associatedTokenProgram: null,
+        systemProgram: SystemProgram.programId,
+      },
+    };
+  };
+
+  const addProductToCart = async (cartPda: PublicKey, cartListPda: PublicKey) => {
+    await program.methods
+      .addToCart(1)
+      .accounts({
+        consumer: signer.publicKey,
+        products: productPda,
+        cart: cartPda,
+        cartList: cartListPda,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc();
+  };
+
+  it("rejects a checkout with mismatched or duplicated cart accounts", async () => {
+    const nonce = PAYMENT_NONCE.addn(1);
+    const { cartListPda, cartPda, accounts } = await checkoutPdas(nonce);
+    await addProductToCart(cartPda, cartListPda);
+
+    const attempts = [
+      // (product, cart) instead of (cart, product)
+      [productPda, cartPda],
+      // the same pair passed twice
+      [cartPda, productPda, cartPda, productPda],
+    ];
+    for (const keys of attempts) {
+      try {
+        await program.methods
+          .checkoutCart(nonce, new BN(Number(PAYMENT_AMOUNT)))
+          .accounts(accounts as any)
+          .remainingAccounts(keys.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+          .rpc();
+        expect.fail("checkout should reject the account list");
+      } catch (error) {
+        expect(String(error)).to.include("CartAccountsMismatch");
+      }
+    }
+  });
+
+  it("checks out a cart into one payment, escrow and order", async () => {
+    const nonce = PAYMENT_NONCE.addn(1);
+    const { cartListPda, cartPda, accounts } = await checkoutPdas(nonce);
+    const stockBefore = (await program.account.product.fetch(productPda)).quantity;
+
+    await program.methods
+      .checkoutCart(nonce, new BN(Number(PAYMENT_AMOUNT)))
+      .accounts(accounts as any)
+      .remainingAccounts([
+        { pubkey: cartPda, isSigner: false, isWritable: true },
+        { pubkey: productPda, isSigner: false, isWritable: true },
+      ])
+      .rpc();
+
+    const payment = await program.account.payment.fetch(accounts.payment);
+    expect(payment.productPubkey).to.be.null;
+    expect(payment.paymentAmount.toNumber()).to.equal(Number(PAYMENT_AMOUNT));
+
+    const escrow = await program.account.escrow.fetch(accounts.escrow);
+    expect(escrow.escrowStatus).to.deep.equal({ fundsReceived: {} });
+    expect(escrow.order.toBase58()).to.equal(accounts.order.toBase58());
+
+    const order = await program.account.order.fetch(accounts.order);
+    expect(order.items).to.have.length(1);
+    expect(order.items[0].product.toBase58()).to.equal(productPda.toBase58());
+    expect(order.items[0].quantity).to.equal(1);
+
+    const product = await program.account.product.fetch(productPda);
+    expect(product.quantity).to.equal(stockBefore - 1);
+    const cartList = await program.account.cartList.fetch(cartListPda);
+    expect(cartList.cartList).to.be.empty;
+  });
+
+  // Negative paths for disputes, refunds, expiry, delivery and reviews run
+  // against fresh checkouts so they don't depend on the flow above.
+  const stranger = Keypair.generate();
+  const productMeta = () => [{ pubkey: productPda, isSigner: false, isWritable: true }];
+  let disputed: Awaited<ReturnType<typeof checkoutPdas>>["accounts"];
+  let shipped: Awaited<ReturnType<typeof checkoutPdas>>["accounts"];
+
+  const expectError = async (tx: Promise<unknown>, code: string) => {
+    try {
+      await tx;
+      expect.fail("transaction should have failed");
+    } catch (error) {
+      expect(String(error)).to.include(code);
+    }
+  };
+
+  const checkoutOne = async (nonce: BN) => {
+    const { cartListPda, cartPda, accounts } = await checkoutPdas(nonce);
+    await addProductToCart(cartPda, cartListPda);
+    await program.methods
+      .checkoutCart(nonce, new BN(Number(PAYMENT_AMOUNT)))
+      .accounts(accounts as any)
+      .remainingAccounts([
+        { pubkey: cartPda, isSigner: false, isWritable: true },
+        ...productMeta(),
+      ])
+      .rpc();
+    return accounts;
+  };
+
+  const exitAccounts = (checkout: typeof disputed) => ({
+    buyerAccount: signer.publicKey,
+    payment: checkout.payment,
+    escrow: checkout.escrow,
+    vaultState: checkout.vaultState,
+    vault: checkout.vault,
+    order: checkout.order,
+    sellerProfile: sellerProfilePda,
+    mint: null,
+    vaultTokenAccount: null,
+    buyerTokenAccount: null,
+    tokenProgram: null,
+    systemProgram: SystemProgram.programId,
+  });
+
+  it("rejects reviews of an order that has not been delivered", async () => {
+    disputed = await checkoutOne(PAYMENT_NONCE.addn(2));
+    const [reviewPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("review"), disputed.order.toBuffer(), productPda.toBuffer()],
+      program.programId
+    );
+    await expectError(
+      program.methods
+        .submitReview(5, Array(32).fill(0), "")
+        .accounts({
+          buyer: signer.publicKey,
+          order: disputed.order,
+          product: productPda,
+          review: reviewPda,
+          config: configPda,
+          systemProgram: SystemProgram.programId,
+        } as any)
+        .rpc(),
+      "OrderNotDelivered"
+    );
+  });
+
+  it("rejects delivery confirmation from anyone but the buyer or courier", async () => {
+    await expectError(
+      program.methods
+        .confirmDelivery()
+        .accounts({
+          signer: stranger.publicKey,
+          config: configPda,
+          treasury: treasuryPda,
+          sellerAccount: signer.publicKey,
+          sellerProfile: sellerProfilePda,
+          payment: disputed.payment,
+          escrow: disputed.escrow,
+          order: disputed.order,
+          vaultState: disputed.vaultState,
+          vault: disputed.vault,
+          mint: null,
+          vaultTokenAccount: null,
+          sellerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        } as any)
+        .signers([stranger])
+        .rpc(),
+      "UnauthorizedOrderUpdate"
+    );
+  });
+
+  it("rejects expiring an escrow before its shipping deadline", async () => {
+    await expectError(
+      program.methods
+        .expireEscrow()
+        .accounts({ cranker: signer.publicKey, ...exitAccounts(disputed) } as any)
+        .remainingAccounts(productMeta())
+        .rpc(),
+      "ShippingDeadlineNotReached"
+    );
+  });
+
+  it("rejects expiring an escrow once the order has shipped", async () => {
+    shipped = await checkoutOne(PAYMENT_NONCE.addn(3));
+    for (const status of [{ inTransit: {} }, { shipped: {} }]) {
+      await program.methods
+        .updateOrder(status as any)
+        .accounts({ signer: signer.publicKey, order: shipped.order, config: configPda } as any)
+        .rpc();
+    }
+    // A shipped order can never expire, whatever the deadline
+    await expectError(
+      program.methods
+        .expireEscrow()
+        .accounts({ cranker: signer.publicKey, ...exitAccounts(shipped) } as any)
+        .remainingAccounts(productMeta())
+        .rpc(),
+      "OrderAlreadyShipped"
+    );
+  });
+
+  it("refunds the buyer and restocks the product", async () => {
+    const stockBefore = (await program.account.product.fetch(productPda)).quantity;
+    await program.methods
+      .refundEscrow()
+      .accounts({ seller: signer.publicKey, ...exitAccounts(shipped) } as any)
+      .remainingAccounts(productMeta())
+      .rpc();
+
+    const escrow = await program.account.escrow.fetch(shipped.escrow);
+    expect(escrow.escrowStatus).to.deep.equal({ transferFailed: {} });
+    const order = await program.account.order.fetch(shipped.order);
+    expect(order.orderStatus).to.deep.equal({ returned: {} });
+    const product = await program.account.product.fetch(productPda);
+    expect(product.quantity).to.equal(stockBefore + 1);
+  });
+
+  it("only lets the arbitrator resolve a dispute", async () => {
+    const [disputePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dispute"), disputed.escrow.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .openDispute()
+      .accounts({
+        signer: signer.publicKey,
+        config: configPda,
+        payment: disputed.payment,
+        escrow: disputed.escrow,
+        dispute: disputePda,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    const resolveAccounts = (arbitrator: PublicKey) => ({
+      ...exitAccounts(disputed),
+      arbitrator,
+      config: configPda,
+      treasury: treasuryPda,
+      sellerAccount: signer.publicKey,
+      dispute: disputePda,
+      sellerTokenAccount: null,
+      treasuryTokenAccount: null,
+    });
+    await expectError(
+      program.methods
+        .resolveDispute(10_000)
+        .accounts(resolveAccounts(stranger.publicKey) as any)
+        .remainingAccounts(productMeta())
+        .signers([stranger])
+        .rpc(),
+      "InvalidArbitrator"
+    );
+
+    // Awarding the buyer everything puts the goods back on sale
+    const stockBefore = (await program.account.product.fetch(productPda)).quantity;
+    await program.methods
+      .resolveDispute(10_000)
+      .accounts(resolveAccounts(signer.publicKey) as any)
+      .remainingAccounts(productMeta())
+      .rpc();
+    const escrow = await program.account.escrow.fetch(disputed.escrow);
+    expect(escrow.escrowStatus).to.deep.equal({ resolved: {} });
+    const product = await program.account.product.fetch(productPda);
+    expect(product.quantity).to.equal(stockBefore + 1);
+  });
+  it("updates the quantity of a cart line and removes it", async () => {
+    const { cartListPda, cartPda } = await checkoutPdas(PAYMENT_NONCE);
+    await addProductToCart(cartPda, cartListPda);
+
+    await program.methods
+      .updateCartQuantity(3)
+      .accounts({
+        consumer: signer.publicKey,
+        products: productPda,
+        cart: cartPda,
+        cartList: cartListPda,
+        config: configPda,
+      } as any)
+      .rpc();
+    const cart = await program.account.cart.fetch(cartPda);
+    expect(cart.quantity.toNumber()).to.equal(3);
+
+    await program.methods
+      .removeFromCart()
+      .accounts({
+        consumer: signer.publicKey,
+        cart: cartPda,
+        cartList: cartListPda,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc();
+    expect(await provider.connection.getAccountInfo(cartPda)).to.be.null;
+    const cartList = await program.account.cartList.fetch(cartListPda);
+    expect(cartList.cartList).to.be.empty;
+  });
+
+  it("clears the cart", async () => {
+    const { cartListPda, cartPda } = await checkoutPdas(PAYMENT_NONCE);
+    await addProductToCart(cartPda, cartListPda);
+
+    await program.methods
+      .clearCart()
+      .accounts({
+        consumer: signer.publicKey,
+        cartList: cartListPda,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .remainingAccounts([{ pubkey: cartPda, isSigner: false, isWritable: true }])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(cartPda)).to.be.null;
+    const cartList = await program.account.cartList.fetch(cartListPda);
+    expect(cartList.cartList).to.be.empty;
+  });
+
+  const updateProductAccounts = () => ({
+    seller: signer.publicKey,
+    product: productPda,
+    config: configPda,
+  });
+
+  it("updates a product's details and stock", async () => {
+    await program.methods
+      .updateProduct(
+        "Updated description",
+        null,
+        "https://example.com/updated.png",
+        20,
+        null
+      )
+      .accounts(updateProductAccounts() as any)
+      .rpc();
+    const product = await program.account.product.fetch(productPda);
+    expect(product.productShortDescription).to.equal("Updated description");
+    expect(product.productImgurl).to.equal("https://example.com/updated.png");
+    expect(product.price).to.equal(Number(PAYMENT_AMOUNT));
+    expect(product.quantity).to.equal(20);
+    expect(product.stockStatus).to.deep.equal({ inStock: {} });
+  });
+
+  it("pauses and resumes listings", async () => {
+    const setListingsPaused = (paused: boolean) =>
+      program.methods
+        .setPause(null, paused, null, null, null)
+        .accounts({ authority: signer.publicKey, config: configPda } as any)
+        .rpc();
+
+    await setListingsPaused(true);
+    expect((await program.account.marketplaceConfig.fetch(configPda)).listingsPaused).to.be.true;
+    await expectError(
+      program.methods.restockProduct(1).accounts(updateProductAccounts() as any).rpc(),
+      "SubsystemPaused"
+    );
+
+    await setListingsPaused(false);
+    expect((await program.account.marketplaceConfig.fetch(configPda)).listingsPaused).to.be.false;
+  });
+
+  it("restocks a product", async () => {
+    const stockBefore = (await program.account.product.fetch(productPda)).quantity;
+    await program.methods
+      .restockProduct(5)
+      .accounts(updateProductAccounts() as any)
+      .rpc();
+    const product = await program.account.product.fetch(productPda);
+    expect(product.quantity).to.equal(stockBefore + 5);
+  });
+
+  it("delists a product and frees its index slots", async () => {
+    const productName = `Delist Test ${PAYMENT_NONCE.toString()}`;
+    const listing = await listingAccounts(productName);
+    await program.methods
+      .createProduct(
+        productName,
+        "Product removed by the delist test",
+        1,
+        { electronics: {} },
+        { laptop: {} },
+        "https://example.com/delist.png",
+        1,
+        null
+      )
+      .accounts(listing as any)
+      .rpc();
+    const listingsBefore = (await program.account.sellerProfile.fetch(sellerProfilePda)).activeListings;
+    const categoryBefore = (await program.account.productIndex.fetch(listing.categoryIndex)).totalProducts;
+
+    // The newest listing sits on the last page of every list, so no last
+    // pages are needed; the page payers only matter if a page empties
+    await program.methods
+      .delistProduct()
+      .accounts({
+        ...listing,
+        lastPage: null,
+        categoryLastPage: null,
+        categoryPagePayer: signer.publicKey,
+        divisionLastPage: null,
+        divisionPagePayer: signer.publicKey,
+      } as any)
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(listing.product)).to.be.null;
+    const profile = await program.account.sellerProfile.fetch(sellerProfilePda);
+    expect(profile.activeListings).to.equal(listingsBefore - 1);
+    const categoryIndex = await program.account.productIndex.fetch(listing.categoryIndex);
+    expect(categoryIndex.totalProducts).to.equal(categoryBefore - 1);
+  });
+
+  let delivered: typeof disputed;
+
+  it("pays the seller and treasury when the buyer confirms delivery", async () => {
+    delivered = await checkoutOne(PAYMENT_NONCE.addn(4));
+    for (const status of [{ inTransit: {} }, { shipped: {} }]) {
+      await program.methods
+        .updateOrder(status as any)
+        .accounts({ signer: signer.publicKey, order: delivered.order, config: configPda } as any)
+        .rpc();
+    }
+    const sellerBefore = await provider.connection.getBalance(signer.publicKey);
+    const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+
+    // The buyer may confirm receipt as soon as the order has shipped
+    const sig = await program.methods
+      .confirmDelivery()
+      .accounts({
+        signer: signer.publicKey,
+        config: configPda,
+        treasury: treasuryPda,
+        sellerAccount: signer.publicKey,
+        sellerProfile: sellerProfilePda,
+        payment: delivered.payment,
+        escrow: delivered.escrow,
+        order: delivered.order,
+        vaultState: delivered.vaultState,
+        vault: delivered.vault,
+        mint: null,
+        vaultTokenAccount: null,
+        sellerTokenAccount: null,
+        treasuryTokenAccount: null,
+        tokenProgram: null,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc({ commitment: "confirmed" });
+
+    const escrow = await program.account.escrow.fetch(delivered.escrow);
+    const expectedFee = Math.floor((Number(PAYMENT_AMOUNT) * PLATFORM_FEE_BPS) / 10_000);
+    expect(escrow.escrowStatus).to.deep.equal({ swapSuccess: {} });
+    expect(escrow.platformFee.toNumber()).to.equal(expectedFee);
+    expect(escrow.sellerPayout.toNumber()).to.equal(Number(PAYMENT_AMOUNT) - expectedFee);
+
+    // The seller is also the fee payer in this suite
+    const tx = await provider.connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const sellerAfter = await provider.connection.getBalance(signer.publicKey);
+    const treasuryAfter = await provider.connection.getBalance(treasuryPda);
+    expect(sellerAfter - sellerBefore).to.equal(escrow.sellerPayout.toNumber() - tx.meta.fee);
+    expect(treasuryAfter - treasuryBefore).to.equal(expectedFee);
+
+    const order = await program.account.order.fetch(delivered.order);
+    expect(order.orderTracking).to.deep.equal({ delivered: {} });
+  });
+
+  it("records a review of a delivered order", async () => {
+    const [reviewPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("review"), delivered.order.toBuffer(), productPda.toBuffer()],
+      program.programId
+    );
+    const before = await program.account.product.fetch(productPda);
+    await program.methods
+      .submitReview(4, Array(32).fill(1), "https://example.com/review.json")
+      .accounts({
+        buyer: signer.publicKey,
+        order: delivered.order,
+        product: productPda,
+        review: reviewPda,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    const review = await program.account.review.fetch(reviewPda);
+    expect(review.score).to.equal(4);
+    const product = await program.account.product.fetch(productPda);
+    expect(product.ratingCount).to.equal(before.ratingCount + 1);
+    expect(product.ratingSum.toNumber()).to.equal(before.ratingSum.toNumber() + 4);
+  });
 });